@@ -0,0 +1,160 @@
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use syn::{
+    Attribute, Error, Ident, LitInt, LitStr, Path, Result, Token, Type, ext::IdentExt as _,
+    parenthesized, parse::ParseStream, punctuated::Punctuated,
+};
+
+// Options specified by `#[futures_enum(...)]` attributes.
+#[derive(Default)]
+pub(crate) struct Args {
+    // `crate = "path"`: the path to the `futures` crate or a crate re-exporting it.
+    pub(crate) crate_: Option<Path>,
+    // `stream = "path"`, `sink = "path"`, `io = "path"`, and `task = "path"`:
+    // the paths to the modules that contain the traits.
+    pub(crate) stream: Option<Path>,
+    pub(crate) sink: Option<Path>,
+    pub(crate) io: Option<Path>,
+    pub(crate) task: Option<Path>,
+    // `assert(Send, Sync, Unpin)`: the auto traits that the type of each variant
+    // must implement.
+    pub(crate) assert: Option<Vec<Ident>>,
+    // `debug`: print the generated code to stderr.
+    pub(crate) debug: Option<()>,
+    // `trace`: emit a `tracing` event for each poll.
+    pub(crate) trace: Option<()>,
+    // `observe = "path"`: the type or module whose `on_poll` and `on_bytes`
+    // functions are called for each poll.
+    pub(crate) observe: Option<Path>,
+    // `catch_unwind = "path"`: the type that panics in the variants are
+    // converted into.
+    pub(crate) catch_unwind: Option<Path>,
+    // `budget = N`: the number of consecutive `Ready` results after which the
+    // read side yields to the executor.
+    pub(crate) budget: Option<LitInt>,
+    // `then = "path"`: the function that is called with the output of the
+    // variant that completed, and returns the output or the next variant.
+    pub(crate) then: Option<Path>,
+    // `output = "Type"`: the output of the future with `then`, which is the `B`
+    // of the `ControlFlow<B, Self>` that the function returns.
+    pub(crate) output: Option<Type>,
+    // `on_end = "path"`: the function that is called when the active variant
+    // of a stream ends, and returns the next variant.
+    pub(crate) on_end: Option<Path>,
+    // `join`: `#[derive(IntoFuture)]` on a struct joins the futures in the
+    // fields.
+    pub(crate) join: Option<Ident>,
+    // The keys of all the options, in the order they are specified.
+    pub(crate) keys: Vec<Ident>,
+}
+
+impl Args {
+    pub(crate) fn new(attrs: &[Attribute]) -> Result<Self> {
+        let mut args = Self::default();
+        for attr in attrs {
+            if attr.path.is_ident("futures_enum") {
+                attr.parse_args_with(|input: ParseStream<'_>| args.parse(input))?;
+            }
+        }
+        match (&args.then, &args.output) {
+            (Some(then), None) => {
+                return Err(Error::new_spanned(
+                    then,
+                    "`then` option requires `output = \"Type\"` option",
+                ));
+            }
+            (None, Some(output)) => {
+                return Err(Error::new_spanned(
+                    output,
+                    "`output` option may only be used with `then` option",
+                ));
+            }
+            _ => {}
+        }
+        Ok(args)
+    }
+
+    fn parse(&mut self, input: ParseStream<'_>) -> Result<()> {
+        while !input.is_empty() {
+            let key = input.call(Ident::parse_any)?;
+            match &*key.to_string() {
+                "crate" => set(&mut self.crate_, &key, path(input)?)?,
+                "stream" => set(&mut self.stream, &key, path(input)?)?,
+                "sink" => set(&mut self.sink, &key, path(input)?)?,
+                "io" => set(&mut self.io, &key, path(input)?)?,
+                "task" => set(&mut self.task, &key, path(input)?)?,
+                "assert" => set(&mut self.assert, &key, auto_traits(input)?)?,
+                "debug" => set(&mut self.debug, &key, ())?,
+                "trace" => {
+                    if cfg!(not(feature = "tracing")) {
+                        return Err(Error::new_spanned(
+                            &key,
+                            "`trace` option requires the `tracing` feature of futures-enum",
+                        ));
+                    }
+                    set(&mut self.trace, &key, ())?;
+                }
+                "observe" => set(&mut self.observe, &key, path(input)?)?,
+                "catch_unwind" => set(&mut self.catch_unwind, &key, path(input)?)?,
+                "budget" => set(&mut self.budget, &key, budget(input)?)?,
+                "then" => set(&mut self.then, &key, path(input)?)?,
+                "output" => set(&mut self.output, &key, ty(input)?)?,
+                "on_end" => set(&mut self.on_end, &key, path(input)?)?,
+                "join" => set(&mut self.join, &key, key.clone())?,
+                _ => return Err(Error::new_spanned(&key, format!("unknown option `{key}`"))),
+            }
+            self.keys.push(key);
+            if input.is_empty() {
+                break;
+            }
+            input.parse::<Token![,]>()?;
+        }
+        Ok(())
+    }
+}
+
+fn set<T>(slot: &mut Option<T>, key: &Ident, value: T) -> Result<()> {
+    if slot.is_some() {
+        return Err(Error::new_spanned(key, format!("duplicate `{key}` option")));
+    }
+    *slot = Some(value);
+    Ok(())
+}
+
+// `= "path"`
+fn path(input: ParseStream<'_>) -> Result<Path> {
+    input.parse::<Token![=]>()?;
+    input.parse::<LitStr>()?.parse()
+}
+
+// `= "Type"`
+fn ty(input: ParseStream<'_>) -> Result<Type> {
+    input.parse::<Token![=]>()?;
+    input.parse::<LitStr>()?.parse()
+}
+
+// `= N`, where N is greater than 0
+fn budget(input: ParseStream<'_>) -> Result<LitInt> {
+    input.parse::<Token![=]>()?;
+    let lit: LitInt = input.parse()?;
+    if lit.base10_parse::<usize>()? == 0 {
+        return Err(Error::new_spanned(lit, "`budget` must be greater than 0"));
+    }
+    Ok(lit)
+}
+
+// `(Send, Sync, Unpin)`
+fn auto_traits(input: ParseStream<'_>) -> Result<Vec<Ident>> {
+    let content;
+    parenthesized!(content in input);
+    let traits = Punctuated::<Ident, Token![,]>::parse_terminated(&content)?;
+    for trait_ in &traits {
+        if trait_ != "Send" && trait_ != "Sync" && trait_ != "Unpin" {
+            return Err(Error::new_spanned(
+                trait_,
+                format!("unknown trait `{trait_}`; expected `Send`, `Sync`, or `Unpin`"),
+            ));
+        }
+    }
+    Ok(traits.into_iter().collect())
+}
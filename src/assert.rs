@@ -0,0 +1,127 @@
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use derive_utils::EnumData;
+use proc_macro2::{Group, Span, TokenStream, TokenTree};
+use quote::{ToTokens as _, format_ident, quote, quote_spanned};
+use syn::{Ident, ItemImpl, Type, parse_quote};
+
+// Asserts that the type of each variant implements `traits`, reporting errors
+// at the variant instead of where the enum is used.
+//
+// Types that refer to type parameters of the enum are not checked, because
+// whether they implement the traits depends on the type arguments; `bounds`
+// requires them in the generated impls instead.
+pub(crate) fn auto_traits(data: &EnumData, traits: &[Ident]) -> TokenStream {
+    let type_params: Vec<_> = data.generics.type_params().map(|p| p.ident.to_string()).collect();
+    let mut fns = vec![];
+    let mut asserts = vec![];
+    for trait_ in traits {
+        // Use the span of the option so that errors are the same regardless of
+        // which derive emits them, and deduplicated by the compiler.
+        let assert =
+            format_ident!("assert_{}", trait_.to_string().to_lowercase(), span = trait_.span());
+        for (v, ty) in data.variants.iter().zip(data.field_types()) {
+            if !refers_to(ty, &type_params) {
+                let ty = respan(ty.to_token_stream(), v.ident.span());
+                asserts.push(quote!(#assert::<#ty>();));
+            }
+        }
+        fns.push(quote_spanned! {trait_.span()=>
+            fn #assert<T: ?::core::marker::Sized + ::core::marker::#trait_>() {}
+        });
+    }
+    if asserts.is_empty() {
+        return TokenStream::new();
+    }
+
+    let (impl_generics, _, where_clause) = data.generics.split_for_impl();
+    quote! {
+        const _: () = {
+            #[allow(dead_code)]
+            fn __assert #impl_generics () #where_clause {
+                #(#fns)*
+                #(#asserts)*
+            }
+        };
+    }
+}
+
+// Requires the types of the variants that `auto_traits` does not check to
+// implement `traits` in `item`, through marker traits that name the variant in
+// the error. Returns the marker traits, which must be in scope of `item`.
+pub(crate) fn bounds(item: &mut ItemImpl, data: &EnumData, traits: &[Ident]) -> TokenStream {
+    let type_params: Vec<_> = data.generics.type_params().map(|p| p.ident.to_string()).collect();
+    let mut markers = TokenStream::new();
+    for trait_ in traits {
+        for (i, (v, ty)) in data.variants.iter().zip(data.field_types()).enumerate() {
+            if !refers_to(ty, &type_params) {
+                continue;
+            }
+            let marker = format_ident!("__Assert{}{}", trait_, i);
+            let message = format!("variant `{}` does not implement `{trait_}`", v.ident);
+            let label = format!("`{{Self}}` does not implement `{trait_}`");
+            let note = format!("required by `#[futures_enum(assert({trait_}))]`");
+            // `do_not_recommend` makes rustc report the marker trait instead
+            // of the auto trait required by its impl.
+            markers.extend(quote! {
+                #[diagnostic::on_unimplemented(message = #message, label = #label, note = #note)]
+                trait #marker {}
+                #[diagnostic::do_not_recommend]
+                impl<T: ?::core::marker::Sized + ::core::marker::#trait_> #marker for T {}
+            });
+            item.generics.make_where_clause().predicates.push(parse_quote!(#ty: #marker));
+        }
+    }
+    markers
+}
+
+/// Variants whose type refers to a type parameter are checked where the impl is
+/// used, and reported with the name of the variant.
+///
+/// ```compile_fail,E0277
+/// // error[E0277]: variant `B` does not implement `Send`
+/// use std::{future::Future, pin::Pin};
+///
+/// #[derive(futures_enum::Future)]
+/// #[futures_enum(assert(Send))]
+/// enum Either<A, B> {
+///     A(A),
+///     B(B),
+/// }
+///
+/// fn spawn<F: Future + Send>(_: F) {}
+///
+/// type Local = Pin<Box<dyn Future<Output = i32>>>;
+/// spawn(async {
+///     Either::<std::future::Ready<i32>, Local>::B(Box::pin(async { 1 })).await
+/// });
+/// ```
+#[cfg(doctest)]
+mod generic_variant {}
+
+// Returns `true` if `ty` refers to `Self` or any of `idents`.
+pub(crate) fn refers_to(ty: &Type, idents: &[String]) -> bool {
+    fn visit(tokens: TokenStream, idents: &[String]) -> bool {
+        tokens.into_iter().any(|tt| match tt {
+            TokenTree::Group(g) => visit(g.stream(), idents),
+            TokenTree::Ident(i) => i == "Self" || idents.iter().any(|p| i == p),
+            TokenTree::Punct(_) | TokenTree::Literal(_) => false,
+        })
+    }
+    visit(ty.to_token_stream(), idents)
+}
+
+// Sets the span of all tokens in `tokens` to `span`, so that errors about the
+// type point to the variant.
+fn respan(tokens: TokenStream, span: Span) -> TokenStream {
+    tokens
+        .into_iter()
+        .map(|mut tt| {
+            if let TokenTree::Group(g) = &tt {
+                tt = Group::new(g.delimiter(), respan(g.stream(), span)).into();
+            }
+            tt.set_span(span);
+            tt
+        })
+        .collect()
+}
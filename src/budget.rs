@@ -0,0 +1,75 @@
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+// `#[futures_enum(budget = N)]`: generates wrappers that make the read side of
+// the enum yield to the executor after a number of consecutive `Ready`
+// results.
+
+use derive_utils::EnumData;
+use proc_macro2::{Span, TokenStream};
+use quote::{format_ident, quote};
+use syn::{Ident, ImplItem, ItemImpl, ItemTrait, LitInt, Path, parse_quote};
+
+use crate::wrapper;
+
+// The methods that the budget applies to.
+const METHODS: &[&str] = &["poll_next", "poll_read", "poll_read_vectored", "poll_fill_buf"];
+
+// Returns the name of the wrapper that `#[derive(Stream)]` generates.
+pub(crate) fn stream_ident(data: &EnumData) -> Ident {
+    format_ident!("{}BudgetedStream", data.ident)
+}
+
+// Returns the name of the wrapper that `#[derive(AsyncRead)]` generates, and
+// that `#[derive(AsyncBufRead)]` implements `AsyncBufRead` for.
+pub(crate) fn reader_ident(data: &EnumData) -> Ident {
+    format_ident!("{}BudgetedReader", data.ident)
+}
+
+// Defines the wrapper `ident`, which counts the consecutive `Ready` results of
+// the enum in `ready`.
+pub(crate) fn define(data: &EnumData, ident: &Ident, kind: &str) -> TokenStream {
+    let ready = Ident::new("ready", Span::call_site());
+    let doc = format!(
+        " A {kind} that wraps [`{}`] and yields to the executor after a number of consecutive \
+         `Ready` results, created by `{ident}::new`.",
+        data.ident
+    );
+    wrapper::define(data, ident, &doc, (&ready, parse_quote!(usize), quote!(0)))
+}
+
+// Implements `trait_def` for the wrapper `ident` so that the methods in
+// `METHODS`, after `budget` consecutive `Ready` results, wake the task and
+// return `Pending` once. The count is kept in the wrapper, so each value has
+// its own budget.
+pub(crate) fn budget(
+    data: &EnumData,
+    ident: &Ident,
+    path: &Path,
+    trait_def: &ItemTrait,
+    budget: &LitInt,
+) -> ItemImpl {
+    let budget: usize = budget.base10_parse().unwrap();
+    let ready = Ident::new("ready", Span::call_site());
+    let this = wrapper::this();
+    let poll = Ident::new("poll", Span::mixed_site());
+    let mut item = wrapper::delegate(data, ident, path, trait_def);
+    for item in &mut item.items {
+        let ImplItem::Method(method) = item else { continue };
+        if !METHODS.contains(&&*method.sig.ident.to_string()) {
+            continue;
+        }
+        let call = wrapper::call(data, path, &method.sig);
+        method.block = parse_quote!({
+            let #this = unsafe { self.get_unchecked_mut() };
+            if #this.#ready >= #budget {
+                #this.#ready = 0;
+                cx.waker().wake_by_ref();
+                return ::core::task::Poll::Pending;
+            }
+            let #poll = unsafe { #call };
+            #this.#ready = if #poll.is_ready() { #this.#ready + 1 } else { 0 };
+            #poll
+        });
+    }
+    item
+}
@@ -0,0 +1,59 @@
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+// `#[futures_enum(on_end = "path")]`: replaces the active variant of a stream
+// when it ends.
+
+use derive_utils::EnumData;
+use proc_macro2::Span;
+use quote::{quote, quote_spanned};
+use syn::{Ident, ImplItem, ItemImpl, Path, parse_quote, spanned::Spanned as _};
+
+// Changes `poll_next` of `item` so that, when the active variant returns
+// `None`, it calls `#on_end(&self)`, and if that returns `Some`, replaces `self`
+// with it and polls again. The upper bound of `size_hint` becomes unknown.
+pub(crate) fn on_end(item: &mut ItemImpl, data: &EnumData, on_end: &Path) {
+    let trait_ = item.trait_.as_ref().unwrap().1.clone();
+    let ident = &data.ident;
+    let x = Ident::new("x", Span::mixed_site());
+    let poll = Ident::new("poll", Span::mixed_site());
+    let next = Ident::new("next", Span::mixed_site());
+    let lower = Ident::new("lower", Span::mixed_site());
+    let call = quote_spanned!(on_end.span()=> #on_end(&*self));
+    for item in &mut item.items {
+        let ImplItem::Method(method) = item else { continue };
+        if method.sig.ident == "size_hint" {
+            let block = &method.block;
+            method.block = parse_quote!({
+                let (#lower, _) = #block;
+                (#lower, ::core::option::Option::None)
+            });
+        } else if method.sig.ident == "poll_next" {
+            let arms = data.variant_idents().zip(data.field_types()).map(|(v, ty)| {
+                quote! {
+                    #ident::#v(#x) => {
+                        <#ty as #trait_>::poll_next(::core::pin::Pin::new_unchecked(#x), cx)
+                    }
+                }
+            });
+            method.sig.inputs[0] = parse_quote!(mut self: ::core::pin::Pin<&mut Self>);
+            method.block = parse_quote!({
+                loop {
+                    let #poll = unsafe {
+                        match self.as_mut().get_unchecked_mut() {
+                            #(#arms)*
+                        }
+                    };
+                    match #poll {
+                        ::core::task::Poll::Ready(::core::option::Option::None) => match #call {
+                            ::core::option::Option::Some(#next) => self.set(#next),
+                            ::core::option::Option::None => {
+                                break ::core::task::Poll::Ready(::core::option::Option::None);
+                            }
+                        },
+                        #poll => break #poll,
+                    }
+                }
+            });
+        }
+    }
+}
@@ -0,0 +1,69 @@
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+// Prints the generated code for debugging, without `cargo expand`.
+
+use std::{
+    env,
+    fs::OpenOptions,
+    io::Write as _,
+    process::{Command, Stdio},
+};
+
+use proc_macro2::TokenStream;
+use syn::Ident;
+
+const DEBUG_ENV: &str = "FUTURES_ENUM_DEBUG";
+
+// Writes the code generated by `#[derive(#derive)]` on `ident` to stderr if
+// `#[futures_enum(debug)]` is specified, and appends it to the file at
+// `FUTURES_ENUM_DEBUG` if the variable is set.
+pub(crate) fn dump(derive: &str, ident: &Ident, tokens: &TokenStream, attr: bool) {
+    let file = env::var_os(DEBUG_ENV).filter(|v| !v.is_empty());
+    if !attr && file.is_none() {
+        return;
+    }
+    let code = format!("// #[derive({derive})] on `{ident}`\n{}\n", format(tokens));
+    if attr {
+        eprint!("{code}");
+    }
+    if let Some(file) = file {
+        let res = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&file)
+            .and_then(|mut f| f.write_all(code.as_bytes()));
+        if let Err(e) = res {
+            eprintln!(
+                "warning: futures-enum: failed to write to `{}` ({DEBUG_ENV}): {e}",
+                file.to_string_lossy()
+            );
+        }
+    }
+}
+
+// Formats `tokens` with rustfmt if it is available, and falls back to the
+// unformatted tokens otherwise.
+fn format(tokens: &TokenStream) -> String {
+    let code = tokens.to_string();
+    let rustfmt = env::var_os("RUSTFMT").unwrap_or_else(|| "rustfmt".into());
+    let child = Command::new(rustfmt)
+        .args(["--edition", "2021", "--emit", "stdout", "--quiet"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn();
+    let Ok(mut child) = child else { return code };
+    // rustfmt reads all of stdin before writing anything, so this cannot
+    // deadlock.
+    if child.stdin.take().unwrap().write_all(code.as_bytes()).is_err() {
+        let _ = child.kill();
+        let _ = child.wait();
+        return code;
+    }
+    match child.wait_with_output() {
+        Ok(output) if output.status.success() && !output.stdout.is_empty() => {
+            String::from_utf8(output.stdout).unwrap_or(code)
+        }
+        _ => code,
+    }
+}
@@ -0,0 +1,155 @@
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use proc_macro2::{Span, TokenStream};
+use quote::quote;
+use syn::{
+    Error, FnArg, Ident, ItemEnum, Pat, Path, Result, Token, TraitItemMethod, Type, braced,
+    parse::{Parse, ParseStream},
+    parse_quote,
+};
+
+// #[delegate_async_trait([?Send,] path::to::Trait { <method signatures> })]
+struct Args {
+    send: bool,
+    path: Path,
+    methods: Vec<TraitItemMethod>,
+}
+
+impl Parse for Args {
+    fn parse(input: ParseStream<'_>) -> Result<Self> {
+        let send = if input.peek(Token![?]) {
+            input.parse::<Token![?]>()?;
+            let send: Ident = input.parse()?;
+            if send != "Send" {
+                return Err(Error::new_spanned(send, "expected `?Send`"));
+            }
+            input.parse::<Token![,]>()?;
+            false
+        } else {
+            true
+        };
+        let path = input.parse()?;
+        let content;
+        braced!(content in input);
+        let mut methods = vec![];
+        while !content.is_empty() {
+            methods.push(content.parse()?);
+        }
+        Ok(Self { send, path, methods })
+    }
+}
+
+pub(crate) fn attribute(args: TokenStream, input: TokenStream) -> TokenStream {
+    expand(args, input).unwrap_or_else(|e| e.to_compile_error())
+}
+
+fn expand(args: TokenStream, input: TokenStream) -> Result<TokenStream> {
+    let Args { send, path, methods } = syn::parse2(args)?;
+    let item: ItemEnum = syn::parse2(input)?;
+
+    if item.variants.is_empty() {
+        return Err(Error::new_spanned(
+            &item.ident,
+            "`#[delegate_async_trait]` may not be used on enums without variants",
+        ));
+    }
+    let mut field_types = Vec::with_capacity(item.variants.len());
+    for v in &item.variants {
+        match &v.fields {
+            syn::Fields::Unnamed(f) if f.unnamed.len() == 1 => field_types.push(&f.unnamed[0].ty),
+            _ => {
+                return Err(Error::new_spanned(
+                    v,
+                    "`#[delegate_async_trait]` requires each variant to have exactly one unnamed field",
+                ));
+            }
+        }
+    }
+
+    let methods = methods
+        .into_iter()
+        .map(|method| delegate_method(&item, &field_types, &path, method))
+        .collect::<Result<Vec<_>>>()?;
+
+    let mut generics = item.generics.clone();
+    let where_clause = generics.make_where_clause();
+    for ty in &field_types {
+        where_clause.predicates.push(parse_quote!(#ty: #path));
+    }
+    let ident = &item.ident;
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    let async_trait = if send {
+        quote!(#[::async_trait::async_trait])
+    } else {
+        quote!(#[::async_trait::async_trait(?Send)])
+    };
+
+    Ok(quote! {
+        #item
+        #async_trait
+        impl #impl_generics #path for #ident #ty_generics #where_clause {
+            #(#methods)*
+        }
+    })
+}
+
+fn delegate_method(
+    item: &ItemEnum,
+    field_types: &[&Type],
+    trait_path: &Path,
+    method: TraitItemMethod,
+) -> Result<TokenStream> {
+    let TraitItemMethod { attrs, sig, .. } = method;
+
+    let mut has_receiver = false;
+    let mut args = vec![];
+    for arg in &sig.inputs {
+        match arg {
+            FnArg::Receiver(_) => has_receiver = true,
+            FnArg::Typed(arg) => match &*arg.pat {
+                Pat::Ident(pat) if pat.ident == "self" => {
+                    return Err(Error::new_spanned(
+                        arg,
+                        "only `self`, `&self` and `&mut self` receivers are supported",
+                    ));
+                }
+                Pat::Ident(pat) => args.push(&pat.ident),
+                _ => {
+                    return Err(Error::new_spanned(
+                        &arg.pat,
+                        "patterns are not supported in delegated method arguments; use an identifier",
+                    ));
+                }
+            },
+        }
+    }
+    if !has_receiver {
+        return Err(Error::new_spanned(
+            &sig.ident,
+            "associated functions without a receiver cannot be delegated",
+        ));
+    }
+
+    let ident = &item.ident;
+    let method = &sig.ident;
+    // `async fn`s are desugared by `#[async_trait]` into the boxed `Send` future
+    // shape, other methods (including the already-desugared form) are
+    // forwarded as-is.
+    let await_ = sig.asyncness.map(|_| quote!(.await));
+    let x = Ident::new("x", Span::mixed_site());
+    let arms = item.variants.iter().zip(field_types).map(|(v, ty)| {
+        let variant = &v.ident;
+        quote! {
+            #ident::#variant(#x) => <#ty as #trait_path>::#method(#x #(, #args)*) #await_,
+        }
+    });
+
+    Ok(quote! {
+        #(#attrs)*
+        #sig {
+            match self {
+                #(#arms)*
+            }
+        }
+    })
+}
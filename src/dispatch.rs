@@ -0,0 +1,354 @@
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use std::mem;
+
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote};
+use syn::{
+    AngleBracketedGenericArguments, Error, Expr, GenericArgument, Ident, ItemFn, PathArguments,
+    Result, ReturnType, Stmt, Type, TypeParamBound, parse_quote,
+    visit_mut::{self, VisitMut},
+};
+
+use crate::{
+    args::Args,
+    path::{Module, trait_path},
+};
+
+// Traits that `#[dispatch]` knows how to derive, and the derives for them.
+const TRAITS: &[(&str, fn(proc_macro::TokenStream) -> proc_macro::TokenStream)] = &[
+    ("Future", crate::derive_future),
+    ("Stream", crate::derive_stream),
+    ("Sink", crate::derive_sink),
+    ("AsyncRead", crate::derive_async_read),
+    ("AsyncWrite", crate::derive_async_write),
+    ("AsyncSeek", crate::derive_async_seek),
+    ("AsyncBufRead", crate::derive_async_buf_read),
+];
+
+pub(crate) fn attribute(args: TokenStream, input: TokenStream) -> TokenStream {
+    expand(args, input).unwrap_or_else(|e| e.to_compile_error())
+}
+
+fn expand(args: TokenStream, input: TokenStream) -> Result<TokenStream> {
+    let into = if args.is_empty() {
+        false
+    } else {
+        const MSG: &str = "expected `#[dispatch]` or `#[dispatch(into)]`";
+        match syn::parse2::<Ident>(args) {
+            Ok(arg) if arg == "into" => true,
+            Ok(arg) => return Err(Error::new_spanned(arg, MSG)),
+            Err(e) => return Err(Error::new(e.span(), MSG)),
+        }
+    };
+    let mut item: ItemFn = syn::parse2(input)?;
+    if let Some(async_token) = &item.sig.asyncness {
+        return Err(Error::new_spanned(
+            async_token,
+            "`#[dispatch]` cannot be used on `async fn`; return `impl Future` instead",
+        ));
+    }
+    let derives = derives(&item.sig.output)?;
+    let into = if into { Some(Conversion::new(&item.sig.output)?) } else { None };
+
+    let mut visitor = Dispatch {
+        ident: format_ident!("__FuturesEnumDispatch"),
+        count: 0,
+        into: into.as_ref().map(Conversion::wrap),
+    };
+    visitor.visit_block_mut(&mut item.block);
+    if let Some(Stmt::Expr(expr)) = item.block.stmts.last_mut() {
+        visitor.visit_tail(expr);
+    }
+    if visitor.count < 2 {
+        return Err(Error::new_spanned(
+            &item.sig.ident,
+            "`#[dispatch]` requires at least two branches returning different types",
+        ));
+    }
+
+    let ident = &visitor.ident;
+    let params: Vec<_> = (0..visitor.count).map(|i| format_ident!("__T{}", i)).collect();
+    let variants: Vec<_> = (0..visitor.count).map(|i| format_ident!("__V{}", i)).collect();
+    let def = quote! {
+        enum #ident<#(#params),*> {
+            #(#variants(#params),)*
+        }
+    };
+    let impls = derives.iter().map(|derive| TokenStream::from(derive(def.clone().into())));
+    let into = into.map(Conversion::define).transpose()?;
+    let stmts = &item.block.stmts;
+    item.block = parse_quote!({
+        #into
+        #def
+        #(#impls)*
+        #(#stmts)*
+    });
+    Ok(quote!(#item))
+}
+
+fn derives(
+    output: &ReturnType,
+) -> Result<Vec<fn(proc_macro::TokenStream) -> proc_macro::TokenStream>> {
+    let mut derives = vec![];
+    if let ReturnType::Type(_, ty) = output {
+        if let Type::ImplTrait(ty) = &**ty {
+            for bound in &ty.bounds {
+                if let TypeParamBound::Trait(bound) = bound {
+                    let name = &bound.path.segments.last().unwrap().ident;
+                    if let Some((_, derive)) = TRAITS.iter().find(|(trait_, _)| name == trait_) {
+                        derives.push(*derive);
+                    }
+                }
+            }
+        }
+    }
+    if derives.is_empty() {
+        return Err(Error::new_spanned(
+            output,
+            "`#[dispatch]` requires a return type of `impl Trait` where `Trait` is one of \
+             `Future`, `Stream`, `Sink`, `AsyncRead`, `AsyncWrite`, `AsyncSeek` or `AsyncBufRead`",
+        ));
+    }
+    Ok(derives)
+}
+
+// `#[dispatch(into)]`: each branch is wrapped in `__FuturesEnumInto`, which
+// converts the `Output`, `Item`, or `Sink::Error` of the branch into the one in
+// the return type with `Into`.
+struct Conversion {
+    // The `Output`, `Item`, and `Error` in the return type, or `None` if the
+    // trait is not in the return type.
+    output: Option<Type>,
+    item: Option<Type>,
+    error: Option<Type>,
+}
+
+impl Conversion {
+    fn new(output: &ReturnType) -> Result<Self> {
+        let mut into = Self { output: None, item: None, error: None };
+        let ReturnType::Type(_, ty) = output else { unreachable!() };
+        let Type::ImplTrait(ty) = &**ty else { unreachable!() };
+        for bound in &ty.bounds {
+            let TypeParamBound::Trait(bound) = bound else { continue };
+            let segment = bound.path.segments.last().unwrap();
+            let name = segment.ident.to_string();
+            let (slot, assoc) = match &*name {
+                "Future" => (&mut into.output, "Output"),
+                "Stream" => (&mut into.item, "Item"),
+                "Sink" => (&mut into.error, "Error"),
+                _ if TRAITS.iter().any(|(trait_, _)| *trait_ == name) => {
+                    return Err(Error::new_spanned(
+                        bound,
+                        format!(
+                            "`#[dispatch(into)]` can only be used with `Future`, `Stream`, and \
+                             `Sink`, not `{name}`"
+                        ),
+                    ));
+                }
+                _ => continue,
+            };
+            let ty = match &segment.arguments {
+                PathArguments::AngleBracketed(AngleBracketedGenericArguments { args, .. }) => {
+                    args.iter().find_map(|arg| match arg {
+                        GenericArgument::Binding(binding) if binding.ident == assoc => {
+                            Some(binding.ty.clone())
+                        }
+                        _ => None,
+                    })
+                }
+                _ => None,
+            };
+            let Some(ty) = ty else {
+                return Err(Error::new_spanned(
+                    bound,
+                    format!(
+                        "`#[dispatch(into)]` requires the type to convert into, \
+                         as in `{name}<{assoc} = T>`"
+                    ),
+                ));
+            };
+            *slot = Some(ty);
+        }
+        Ok(into)
+    }
+
+    // The generic arguments of `__FuturesEnumInto` after the type of the branch.
+    fn wrap(&self) -> TokenStream {
+        let unit: Type = parse_quote!(());
+        let output = self.output.as_ref().unwrap_or(&unit);
+        let item = self.item.as_ref().unwrap_or(&unit);
+        let error = self.error.as_ref().unwrap_or(&unit);
+        quote!(#output, #item, #error)
+    }
+
+    // Defines `__FuturesEnumInto` and implements the traits in the return type
+    // for it.
+    //
+    // SAFETY: `__FuturesEnumInto` pins its field structurally: it does not
+    // implement `Drop`, it is `Unpin` only when the field is (`PhantomData<fn()
+    // -> _>` is always `Unpin`), and the field is never moved out of a pinned
+    // `__FuturesEnumInto`. So `map_unchecked_mut` to the field is sound.
+    fn define(self) -> Result<TokenStream> {
+        let mut tokens = quote! {
+            struct __FuturesEnumInto<__F, __O, __I, __E>(
+                __F,
+                ::core::marker::PhantomData<fn() -> (__O, __I, __E)>,
+            );
+        };
+        let into = quote!(__FuturesEnumInto<__F, __O, __I, __E>);
+        let inner = quote!(unsafe { self.map_unchecked_mut(|this| &mut this.0) });
+        if self.output.is_some() {
+            tokens.extend(quote! {
+                #[allow(unsafe_code)]
+                impl<__F, __O, __I, __E> ::core::future::Future for #into
+                where
+                    __F: ::core::future::Future,
+                    __F::Output: ::core::convert::Into<__O>,
+                {
+                    type Output = __O;
+                    #[inline]
+                    fn poll(
+                        self: ::core::pin::Pin<&mut Self>,
+                        cx: &mut ::core::task::Context<'_>,
+                    ) -> ::core::task::Poll<Self::Output> {
+                        ::core::future::Future::poll(#inner, cx).map(::core::convert::Into::into)
+                    }
+                }
+            });
+        }
+        if self.item.is_some() {
+            let stream = trait_path(&Args::default(), Module::Stream, "Stream")?;
+            tokens.extend(quote! {
+                #[allow(unsafe_code)]
+                impl<__F, __O, __I, __E> #stream for #into
+                where
+                    __F: #stream,
+                    __F::Item: ::core::convert::Into<__I>,
+                {
+                    type Item = __I;
+                    #[inline]
+                    fn poll_next(
+                        self: ::core::pin::Pin<&mut Self>,
+                        cx: &mut ::core::task::Context<'_>,
+                    ) -> ::core::task::Poll<::core::option::Option<Self::Item>> {
+                        #stream::poll_next(#inner, cx)
+                            .map(|x| x.map(::core::convert::Into::into))
+                    }
+                    #[inline]
+                    fn size_hint(&self) -> (usize, ::core::option::Option<usize>) {
+                        #stream::size_hint(&self.0)
+                    }
+                }
+            });
+        }
+        if self.error.is_some() {
+            let sink = trait_path(&Args::default(), Module::Sink, "Sink")?;
+            let methods =
+                ["poll_ready", "poll_flush", "poll_close"].map(|m| format_ident!("{}", m));
+            tokens.extend(quote! {
+                #[allow(unsafe_code)]
+                impl<__F, __Item, __O, __I, __E> #sink<__Item> for #into
+                where
+                    __F: #sink<__Item>,
+                    __F::Error: ::core::convert::Into<__E>,
+                {
+                    type Error = __E;
+                    #(
+                        #[inline]
+                        fn #methods(
+                            self: ::core::pin::Pin<&mut Self>,
+                            cx: &mut ::core::task::Context<'_>,
+                        ) -> ::core::task::Poll<::core::result::Result<(), Self::Error>> {
+                            #sink::#methods(#inner, cx).map_err(::core::convert::Into::into)
+                        }
+                    )*
+                    #[inline]
+                    fn start_send(
+                        self: ::core::pin::Pin<&mut Self>,
+                        item: __Item,
+                    ) -> ::core::result::Result<(), Self::Error> {
+                        #sink::start_send(#inner, item).map_err(::core::convert::Into::into)
+                    }
+                }
+            });
+        }
+        Ok(tokens)
+    }
+}
+
+struct Dispatch {
+    ident: Ident,
+    count: usize,
+    // The generic arguments of `__FuturesEnumInto` for `#[dispatch(into)]`.
+    into: Option<TokenStream>,
+}
+
+impl Dispatch {
+    // Wraps each expression that can become the value of `expr` in a variant of
+    // the generated enum.
+    fn visit_tail(&mut self, expr: &mut Expr) {
+        match expr {
+            // `if` without `else` evaluates to `()`, so leave the error to the
+            // compiler.
+            Expr::If(expr) => {
+                if let Some((_, else_branch)) = &mut expr.else_branch {
+                    self.visit_block_tail(&mut expr.then_branch.stmts);
+                    self.visit_tail(else_branch);
+                }
+            }
+            Expr::Match(expr) => {
+                for arm in &mut expr.arms {
+                    self.visit_tail(&mut arm.body);
+                }
+            }
+            Expr::Block(expr) => self.visit_block_tail(&mut expr.block.stmts),
+            Expr::Unsafe(expr) => self.visit_block_tail(&mut expr.block.stmts),
+            Expr::Paren(expr) => self.visit_tail(&mut expr.expr),
+            // These diverge, and `return` is handled by `visit_expr_mut`.
+            Expr::Return(_) | Expr::Break(_) | Expr::Continue(_) => {}
+            Expr::Macro(expr)
+                if ["panic", "unreachable", "unimplemented", "todo"]
+                    .iter()
+                    .any(|name| expr.mac.path.is_ident(name)) => {}
+            _ => {
+                let ident = &self.ident;
+                let variant = format_ident!("__V{}", self.count);
+                self.count += 1;
+                let mut inner = mem::replace(expr, Expr::Verbatim(TokenStream::new()));
+                if let Some(into) = &self.into {
+                    inner = parse_quote! {
+                        __FuturesEnumInto::<_, #into>(#inner, ::core::marker::PhantomData)
+                    };
+                }
+                *expr = parse_quote!(#ident::#variant(#inner));
+            }
+        }
+    }
+
+    fn visit_block_tail(&mut self, stmts: &mut [Stmt]) {
+        if let Some(Stmt::Expr(expr)) = stmts.last_mut() {
+            self.visit_tail(expr);
+        }
+    }
+}
+
+impl VisitMut for Dispatch {
+    fn visit_expr_mut(&mut self, expr: &mut Expr) {
+        match expr {
+            // `return` in these returns from a different function.
+            Expr::Closure(_) | Expr::Async(_) => {}
+            Expr::Return(expr) => {
+                if let Some(expr) = &mut expr.expr {
+                    self.visit_expr_mut(expr);
+                    self.visit_tail(expr);
+                }
+            }
+            _ => visit_mut::visit_expr_mut(self, expr),
+        }
+    }
+
+    fn visit_item_mut(&mut self, _: &mut syn::Item) {
+        // Do not visit nested items.
+    }
+}
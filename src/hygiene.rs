@@ -0,0 +1,83 @@
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use proc_macro2::Span;
+use syn::{GenericParam, Generics, Ident, ItemTrait, Lifetime, TraitItem, visit_mut::VisitMut};
+
+// Renames the generic parameters of `trait_def` and its methods that have the
+// same name as a generic parameter of the enum, since they are added to the
+// generics of the impl and its methods.
+pub(crate) fn rename_generics(trait_def: &mut ItemTrait, generics: &Generics) {
+    let methods = trait_def.items.iter().filter_map(|item| match item {
+        TraitItem::Method(method) => Some(method),
+        _ => None,
+    });
+    let params: Vec<_> = trait_def
+        .generics
+        .params
+        .iter()
+        .chain(methods.flat_map(|method| &method.sig.generics.params))
+        .collect();
+    let enum_params: Vec<_> = generics.params.iter().map(param_name).collect();
+    let mut used: Vec<_> =
+        enum_params.iter().cloned().chain(params.iter().map(|p| param_name(p))).collect();
+    let mut rename = Rename::default();
+    for param in params {
+        let name = param_name(param);
+        if !enum_params.contains(&name) {
+            continue;
+        }
+        let fresh = fresh(&name, &used);
+        let (old, new) =
+            (Ident::new(&name, Span::call_site()), Ident::new(&fresh, Span::call_site()));
+        match param {
+            GenericParam::Lifetime(_) => rename.lifetimes.push((old, new)),
+            GenericParam::Type(_) | GenericParam::Const(_) => rename.idents.push((old, new)),
+        }
+        used.push(fresh);
+    }
+    if !rename.lifetimes.is_empty() || !rename.idents.is_empty() {
+        rename.visit_item_trait_mut(trait_def);
+    }
+}
+
+// Returns a lifetime named `'#name` that is not a generic parameter of the
+// enum, for the generics that the expansion adds.
+pub(crate) fn fresh_lifetime(name: &str, generics: &Generics) -> Lifetime {
+    let used: Vec<_> = generics.params.iter().map(param_name).collect();
+    let name = if used.iter().any(|n| n == name) { fresh(name, &used) } else { name.to_owned() };
+    Lifetime::new(&format!("'{name}"), Span::call_site())
+}
+
+// Returns `name` followed by a number, which is not in `used`.
+fn fresh(name: &str, used: &[String]) -> String {
+    // One of `used.len() + 1` candidates is always unused.
+    (1..=used.len() + 1).map(|i| format!("{name}{i}")).find(|n| !used.contains(n)).unwrap()
+}
+
+fn param_name(param: &GenericParam) -> String {
+    match param {
+        GenericParam::Lifetime(param) => param.lifetime.ident.to_string(),
+        GenericParam::Type(param) => param.ident.to_string(),
+        GenericParam::Const(param) => param.ident.to_string(),
+    }
+}
+
+#[derive(Default)]
+struct Rename {
+    lifetimes: Vec<(Ident, Ident)>,
+    idents: Vec<(Ident, Ident)>,
+}
+
+impl VisitMut for Rename {
+    fn visit_lifetime_mut(&mut self, lifetime: &mut Lifetime) {
+        if let Some((_, new)) = self.lifetimes.iter().find(|(old, _)| lifetime.ident == *old) {
+            lifetime.ident = new.clone();
+        }
+    }
+
+    fn visit_ident_mut(&mut self, ident: &mut Ident) {
+        if let Some((_, new)) = self.idents.iter().find(|(old, _)| ident == old) {
+            *ident = new.clone();
+        }
+    }
+}
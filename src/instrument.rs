@@ -0,0 +1,97 @@
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+// Instruments the poll methods of the generated impls.
+
+use derive_utils::EnumData;
+use proc_macro2::{Span, TokenStream};
+use quote::{quote, quote_spanned};
+use syn::{Ident, ImplItem, ItemImpl, ReturnType, Type, parse_quote, spanned::Spanned as _};
+
+use crate::args::Args;
+
+// Methods whose `Poll<io::Result<usize>>` is the number of bytes moved.
+const BYTES_METHODS: &[&str] =
+    &["poll_read", "poll_read_vectored", "poll_write", "poll_write_vectored"];
+
+// Wraps each method of `item` that returns `Poll` so that it reports the
+// active variant and the outcome of the poll, as specified by `args`.
+pub(crate) fn instrument(item: &mut ItemImpl, data: &EnumData, args: &Args) {
+    if args.trace.is_none() && args.observe.is_none() {
+        return;
+    }
+    let name = data.ident.to_string();
+    let variant_name = variant_name(data, &quote!(*self));
+    let variant = Ident::new("variant", Span::mixed_site());
+    let poll = Ident::new("poll", Span::mixed_site());
+    let start = Ident::new("start", Span::mixed_site());
+    let bytes = Ident::new("bytes", Span::mixed_site());
+    for item in &mut item.items {
+        let ImplItem::Method(method) = item else { continue };
+        if !returns_poll(&method.sig.output) {
+            continue;
+        }
+        let method_name = method.sig.ident.to_string();
+        let mut before = vec![];
+        let mut after = vec![];
+        if args.trace.is_some() {
+            after.push(quote! {
+                ::tracing::trace!(
+                    enum_name = #name,
+                    variant = #variant,
+                    method = #method_name,
+                    poll = if #poll.is_ready() { "Ready" } else { "Pending" },
+                );
+            });
+        }
+        if let Some(observer) = &args.observe {
+            before.push(quote!(let #start = ::std::time::Instant::now();));
+            after.push(quote_spanned! {observer.span()=>
+                #observer::on_poll(
+                    #name,
+                    #variant,
+                    #method_name,
+                    #start.elapsed(),
+                    #poll.is_ready(),
+                );
+            });
+            if BYTES_METHODS.contains(&&*method_name) {
+                after.push(quote_spanned! {observer.span()=>
+                    if let ::core::task::Poll::Ready(::core::result::Result::Ok(#bytes)) = &#poll {
+                        #observer::on_bytes(#name, #variant, #method_name, *#bytes);
+                    }
+                });
+            }
+        }
+        let block = &method.block;
+        method.block = parse_quote!({
+            let #variant = #variant_name;
+            #(#before)*
+            let #poll = #block;
+            #(#after)*
+            #poll
+        });
+    }
+}
+
+// Returns an expression that evaluates to the name of the active variant of
+// the enum `this`.
+pub(crate) fn variant_name(data: &EnumData, this: &TokenStream) -> TokenStream {
+    let ident = &data.ident;
+    let variants = data.variant_idents();
+    let names = data.variant_idents().map(ToString::to_string);
+    quote! {
+        match &#this {
+            #(#ident::#variants(_) => #names,)*
+        }
+    }
+}
+
+fn returns_poll(output: &ReturnType) -> bool {
+    match output {
+        ReturnType::Type(_, ty) => match &**ty {
+            Type::Path(ty) => ty.path.segments.last().is_some_and(|s| s.ident == "Poll"),
+            _ => false,
+        },
+        ReturnType::Default => false,
+    }
+}
@@ -0,0 +1,176 @@
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+// `#[futures_enum(join)]`: `#[derive(IntoFuture)]` on structs, which joins the
+// futures in the fields.
+
+use proc_macro2::{Span, TokenStream};
+use quote::{format_ident, quote};
+use syn::{
+    Data, DataStruct, DeriveInput, Error, Fields, Ident, Index, Member, Result, WherePredicate,
+    parse_quote,
+};
+
+use crate::args::Args;
+
+// The options that may be used with `join`. The others change the impls of
+// the derives on enums, which this does not generate.
+const OPTIONS: &[&str] = &["join", "debug"];
+
+// Returns the expansion of `#[derive(IntoFuture)]` on a struct with
+// `#[futures_enum(join)]`, or `None` if `input` is not such a struct.
+pub(crate) fn expand(input: &proc_macro::TokenStream) -> Option<TokenStream> {
+    let input: DeriveInput = syn::parse(input.clone()).ok()?;
+    let Data::Struct(data) = &input.data else { return None };
+    let args = match Args::new(&input.attrs) {
+        Ok(args) => args,
+        Err(e) => return Some(e.to_compile_error()),
+    };
+    args.join.as_ref()?;
+    let tokens = match args.keys.iter().find(|key| !OPTIONS.contains(&&*key.to_string())) {
+        Some(key) => Err(Error::new_spanned(
+            key,
+            format!("`{key}` option may not be used with `join` option"),
+        )),
+        None => derive(&input, data),
+    };
+    let tokens = tokens.unwrap_or_else(|e| e.to_compile_error());
+    crate::debug::dump("IntoFuture", &input.ident, &tokens, args.debug.is_some());
+    Some(tokens)
+}
+
+// Generates `<Struct>Output`, which has the same shape as the struct and holds
+// the outputs of the fields, and `<Struct>Join`, a future that polls all the
+// fields and completes with `<Struct>Output` when they have all completed. The
+// struct implements `IntoFuture` with `<Struct>Join`, because it has no room
+// for the outputs of the fields that have already completed.
+fn derive(input: &DeriveInput, data: &DataStruct) -> Result<TokenStream> {
+    if data.fields.is_empty() {
+        return Err(Error::new_spanned(
+            &input.ident,
+            "`#[futures_enum(join)]` requires a struct with at least one field",
+        ));
+    }
+    let ident = &input.ident;
+    let vis = &input.vis;
+    let output = format_ident!("{}Output", ident);
+    let join = format_ident!("{}Join", ident);
+    let state = format_ident!("{}JoinState", ident);
+
+    let mut generics = input.generics.clone();
+    generics.make_where_clause().predicates.extend(data.fields.iter().map(|f| -> WherePredicate {
+        let ty = &f.ty;
+        parse_quote!(#ty: ::core::future::Future)
+    }));
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    let members: Vec<Member> = data
+        .fields
+        .iter()
+        .enumerate()
+        .map(|(i, f)| match &f.ident {
+            Some(ident) => Member::Named(ident.clone()),
+            None => Member::Unnamed(Index::from(i)),
+        })
+        .collect();
+    let types: Vec<_> = data.fields.iter().map(|f| &f.ty).collect();
+
+    let output_fields = data.fields.iter().map(|f| {
+        let vis = &f.vis;
+        let ty = &f.ty;
+        let name = f.ident.as_ref().map(|ident| quote!(#ident:));
+        quote!(#vis #name <#ty as ::core::future::Future>::Output)
+    });
+    let join_fields = data.fields.iter().map(|f| {
+        let ty = &f.ty;
+        let name = f.ident.as_ref().map(|ident| quote!(#ident:));
+        quote!(#name #state<#ty>)
+    });
+    let (output_def, join_def) = match &data.fields {
+        Fields::Named(_) => (
+            quote!(#vis struct #output #generics #where_clause { #(#output_fields,)* }),
+            quote!(#vis struct #join #generics #where_clause { #(#join_fields,)* }),
+        ),
+        _ => (
+            quote!(#vis struct #output #generics (#(#output_fields,)*) #where_clause;),
+            quote!(#vis struct #join #generics (#(#join_fields,)*) #where_clause;),
+        ),
+    };
+    let output_doc = format!(" The outputs of the fields of [`{ident}`].");
+    let join_doc = format!(
+        " The future that [`{ident}`] is converted into by `IntoFuture`, which completes when \
+         all the fields have completed."
+    );
+    let polled_after_completion = format!("`{join}` polled after completion");
+    let this = Ident::new("this", Span::mixed_site());
+    let x = Ident::new("x", Span::mixed_site());
+    let done = Ident::new("done", Span::mixed_site());
+
+    // SAFETY of the `unsafe` blocks in `poll`:
+    // - `get_unchecked_mut` is sound because the join future is never moved out
+    //   of: only the fields are accessed through it, and a field in the
+    //   `Future` state is never moved, only replaced (which drops the future in
+    //   place) once it has completed. The outputs, which are moved out by
+    //   `mem::replace`, are not pinned. The join future has no `Drop` impl, and
+    //   it is `Unpin` only when all the futures (and their outputs) are.
+    // - `Pin::new_unchecked` is sound for the same reason: `x` points into the
+    //   pinned join future, and the future that it points to stays there until
+    //   it is dropped.
+    Ok(quote! {
+        #[doc = #output_doc]
+        #output_def
+
+        #[doc = #join_doc]
+        #join_def
+
+        // Like `futures::future::MaybeDone`.
+        enum #state<F: ::core::future::Future> {
+            Future(F),
+            Done(F::Output),
+            Gone,
+        }
+
+        impl #impl_generics ::core::future::IntoFuture for #ident #ty_generics #where_clause {
+            type Output = #output #ty_generics;
+            type IntoFuture = #join #ty_generics;
+            #[inline]
+            fn into_future(self) -> Self::IntoFuture {
+                #join {
+                    #(#members: #state::Future(self.#members),)*
+                }
+            }
+        }
+
+        #[allow(unsafe_code)]
+        impl #impl_generics ::core::future::Future for #join #ty_generics #where_clause {
+            type Output = #output #ty_generics;
+            fn poll(
+                self: ::core::pin::Pin<&mut Self>,
+                cx: &mut ::core::task::Context<'_>,
+            ) -> ::core::task::Poll<Self::Output> {
+                let #this = unsafe { self.get_unchecked_mut() };
+                let mut #done = true;
+                #(
+                    if let #state::Future(#x) = &mut #this.#members {
+                        match <#types as ::core::future::Future>::poll(
+                            unsafe { ::core::pin::Pin::new_unchecked(#x) },
+                            cx,
+                        ) {
+                            ::core::task::Poll::Ready(#x) => #this.#members = #state::Done(#x),
+                            ::core::task::Poll::Pending => #done = false,
+                        }
+                    }
+                )*
+                if !#done {
+                    return ::core::task::Poll::Pending;
+                }
+                ::core::task::Poll::Ready(#output {
+                    #(
+                        #members: match ::core::mem::replace(&mut #this.#members, #state::Gone) {
+                            #state::Done(#x) => #x,
+                            _ => ::core::panic!(#polled_after_completion),
+                        },
+                    )*
+                })
+            }
+        }
+    })
+}
@@ -40,19 +40,490 @@ fn foo(x: i32) -> impl Future<Output = i32> {
 ```
 
 futures-enum works well even if the dependency contains only sub-crates such
-as `futures-core`, `futures-io`, `futures-sink`, etc.
+as `futures-core`, `futures-io`, `futures-sink`, `futures-task`, etc.
 
-See [auto_enums] crate for how to automate patterns like this.
+`#[dispatch]` automates patterns like this; see also [auto_enums] crate for a
+more general version.
 
 ## Supported traits
 
 - [`Future`](https://doc.rust-lang.org/std/future/trait.Future.html) - [example](https://github.com/taiki-e/futures-enum/blob/HEAD/tests/expand/future.rs) | [generated code](https://github.com/taiki-e/futures-enum/blob/HEAD/tests/expand/future.expanded.rs)
+- [`IntoFuture`](https://doc.rust-lang.org/std/future/trait.IntoFuture.html) - [example](https://github.com/taiki-e/futures-enum/blob/HEAD/tests/expand/into_future.rs) | [generated code](https://github.com/taiki-e/futures-enum/blob/HEAD/tests/expand/into_future.expanded.rs)
 - [`Stream`](https://docs.rs/futures/latest/futures/stream/trait.Stream.html) - [example](https://github.com/taiki-e/futures-enum/blob/HEAD/tests/expand/stream.rs) | [generated code](https://github.com/taiki-e/futures-enum/blob/HEAD/tests/expand/stream.expanded.rs)
 - [`Sink`](https://docs.rs/futures/latest/futures/sink/trait.Sink.html) - [example](https://github.com/taiki-e/futures-enum/blob/HEAD/tests/expand/sink.rs) | [generated code](https://github.com/taiki-e/futures-enum/blob/HEAD/tests/expand/sink.expanded.rs)
 - [`AsyncRead`](https://docs.rs/futures/latest/futures/io/trait.AsyncRead.html) - [example](https://github.com/taiki-e/futures-enum/blob/HEAD/tests/expand/async_read.rs) | [generated code](https://github.com/taiki-e/futures-enum/blob/HEAD/tests/expand/async_read.expanded.rs)
 - [`AsyncWrite`](https://docs.rs/futures/latest/futures/io/trait.AsyncWrite.html) - [example](https://github.com/taiki-e/futures-enum/blob/HEAD/tests/expand/async_write.rs) | [generated code](https://github.com/taiki-e/futures-enum/blob/HEAD/tests/expand/async_write.expanded.rs)
 - [`AsyncSeek`](https://docs.rs/futures/latest/futures/io/trait.AsyncSeek.html) - [example](https://github.com/taiki-e/futures-enum/blob/HEAD/tests/expand/async_seek.rs) | [generated code](https://github.com/taiki-e/futures-enum/blob/HEAD/tests/expand/async_seek.expanded.rs)
 - [`AsyncBufRead`](https://docs.rs/futures/latest/futures/io/trait.AsyncBufRead.html) - [example](https://github.com/taiki-e/futures-enum/blob/HEAD/tests/expand/async_buf_read.rs) | [generated code](https://github.com/taiki-e/futures-enum/blob/HEAD/tests/expand/async_buf_read.expanded.rs)
+- [`AsyncIterator`](https://doc.rust-lang.org/nightly/std/async_iter/trait.AsyncIterator.html) *(requires the `async-iterator` feature and nightly)* - [example](https://github.com/taiki-e/futures-enum/blob/HEAD/tests/expand/async_iterator.rs) | [generated code](https://github.com/taiki-e/futures-enum/blob/HEAD/tests/expand/async_iterator.expanded.rs)
+- [`Coroutine`](https://doc.rust-lang.org/nightly/core/ops/trait.Coroutine.html) *(requires the `coroutine` feature and nightly)* - [example](https://github.com/taiki-e/futures-enum/blob/HEAD/tests/expand/coroutine.rs) | [generated code](https://github.com/taiki-e/futures-enum/blob/HEAD/tests/expand/coroutine.expanded.rs)
+- [`Spawn`](https://docs.rs/futures/latest/futures/task/trait.Spawn.html) - [example](https://github.com/taiki-e/futures-enum/blob/HEAD/tests/expand/spawn.rs) | [generated code](https://github.com/taiki-e/futures-enum/blob/HEAD/tests/expand/spawn.expanded.rs)
+- [`LocalSpawn`](https://docs.rs/futures/latest/futures/task/trait.LocalSpawn.html) - [example](https://github.com/taiki-e/futures-enum/blob/HEAD/tests/expand/local_spawn.rs) | [generated code](https://github.com/taiki-e/futures-enum/blob/HEAD/tests/expand/local_spawn.expanded.rs)
+
+`#[derive(IntoFuture)]` generates a companion enum named `<Enum>IntoFuture`
+that holds the `IntoFuture::IntoFuture` of each variant and implements `Future`.
+It is used as `type IntoFuture`, so an enum of types that only implement
+`IntoFuture` can be `.await`ed. Do not combine it with `#[derive(Future)]`.
+
+## Variant names and `Debug`
+
+The futures, streams, etc. held by the variants rarely implement `Debug`, so
+two derives that do not require anything of the variants are also provided:
+
+- `#[derive(VariantName)]` generates inherent `variant_name` and
+  `variant_index` methods that return the name of the active variant and its
+  index in declaration order. The methods have the same visibility as the enum.
+- `#[derive(OpaqueDebug)]` implements `Debug` that prints the active variant
+  without its value, like `Either::A(..)`.
+
+```
+use futures_enum::{Future, OpaqueDebug, VariantName};
+
+#[derive(Future, VariantName, OpaqueDebug)]
+enum Either<A, B> {
+    A(A),
+    B(B),
+}
+
+let x = Either::<_, std::future::Pending<()>>::A(async {});
+assert_eq!(x.variant_name(), "A");
+assert_eq!(x.variant_index(), 0);
+assert_eq!(format!("{x:?}"), "Either::A(..)");
+```
+
+## Joining futures
+
+With `#[futures_enum(join)]`, `#[derive(IntoFuture)]` can also be used on
+structs whose fields are futures. It generates a struct named `<Struct>Output`
+with the same fields that holds their outputs, and a future named
+`<Struct>Join` that polls all the fields and completes with `<Struct>Output`
+when they have all completed. Unlike `futures::join!`, the future can be named,
+and unlike `join_all`, the fields can have different types without boxing. A
+struct has no room for the outputs of the fields that have already completed,
+so the struct implements `IntoFuture` with `<Struct>Join` instead of `Future`.
+The other options, except `debug`, may not be used with `join`.
+
+```
+use futures_enum::IntoFuture;
+
+#[derive(IntoFuture)]
+#[futures_enum(join)]
+struct Requests<U, P> {
+    user: U,
+    posts: P,
+}
+
+async fn fetch() {
+    let requests = Requests { user: async { "alice" }, posts: async { vec![1, 2] } };
+    let RequestsOutput { user, posts } = requests.await;
+    assert_eq!((user, posts), ("alice", vec![1, 2]));
+}
+```
+
+## Path to futures
+
+By default, the derives detect the path to `futures` (or its sub-crates) from
+Cargo.toml, and use `::futures` if the `renamed` feature is disabled. If the
+crate does not depend on `futures`, the `Stream` and IO traits re-exported by
+[`futures-lite`][futures-lite], [`smol`][smol], and [`async-std`][async-std]
+are also detected, and an error is reported if none of them is a dependency.
+To use a different path, for example a crate that re-exports `futures`, use the
+`#[futures_enum(...)]` attribute:
+
+```
+use futures_enum::{AsyncRead, Stream};
+
+mod facade {
+    pub use futures;
+}
+
+#[derive(Stream, AsyncRead)]
+#[futures_enum(crate = "facade::futures")]
+enum Either<A, B> {
+    A(A),
+    B(B),
+}
+```
+
+The following options are supported:
+
+- `crate = "path"`: the path to `futures`. The traits are referenced as
+  `path::stream::Stream`, `path::io::AsyncRead`, etc.
+- `stream = "path"`, `sink = "path"`, `io = "path"`, `task = "path"`: the path
+  to the module that contains the traits, for example `io = "futures_io"`.
+  These take precedence over `crate`.
+
+To use the same path everywhere in a project, set the following environment
+variables instead. They are used when the item has none of the options above,
+and do not require the `renamed` feature, which makes them also usable in build
+systems other than Cargo.
+
+- `FUTURES_ENUM_FUTURES`: the path to `futures`, like `crate = "path"`.
+- `FUTURES_ENUM_FUTURES_CORE`, `FUTURES_ENUM_FUTURES_SINK`,
+  `FUTURES_ENUM_FUTURES_IO`, `FUTURES_ENUM_FUTURES_TASK`: the path to
+  `futures-core`, `futures-sink`, `futures-io`, and `futures-task`. These take
+  precedence over `FUTURES_ENUM_FUTURES`.
+
+With Cargo, these can be set in the [`[env]`][cargo-env] table of
+`.cargo/config.toml`:
+
+```toml
+[env]
+FUTURES_ENUM_FUTURES = "::my_runtime::futures"
+```
+
+Note that Cargo does not rebuild crates that use the derives when these
+variables change; run `cargo clean` after changing them.
+
+## Debugging the generated code
+
+`#[futures_enum(debug)]` prints the code generated by each derive on the item
+to stderr at compile time. To write the code generated for every item to a file
+instead, set the `FUTURES_ENUM_DEBUG` environment variable to the path of the
+file; the code is appended to it. The code is formatted with `rustfmt` if it is
+found in `PATH` (or at `RUSTFMT`), so neither `cargo expand` nor a nightly
+toolchain is needed.
+
+```sh
+FUTURES_ENUM_DEBUG=/tmp/futures-enum.rs cargo build
+```
+
+As with the other variables, Cargo does not rebuild crates when
+`FUTURES_ENUM_DEBUG` changes; run `cargo clean -p <crate>` first.
+
+## Tracing polls
+
+With the `tracing` feature enabled, `#[futures_enum(trace)]` makes each
+generated method that returns `Poll` (`poll`, `poll_next`, `poll_read`,
+`poll_write`, `poll_ready`, etc.) emit a [`tracing`][tracing] event at the
+`TRACE` level, with the following fields:
+
+- `enum_name`: the name of the enum.
+- `variant`: the name of the active variant.
+- `method`: the name of the method, such as `poll_next`.
+- `poll`: `"Ready"` or `"Pending"`.
+
+The generated code refers to `::tracing`, so the crate using the derive must
+also depend on `tracing`:
+
+```toml
+[dependencies]
+futures-enum = { version = "0.1", features = ["tracing"] }
+tracing = "0.1"
+```
+
+## Observing polls
+
+`#[futures_enum(observe = "path")]` calls the functions below of the given type
+or module after each generated method that returns `Poll`, for example to
+record metrics per variant. `on_bytes` is called only by `poll_read`,
+`poll_write`, and their vectored versions, when they return the number of
+bytes read or written. The generated code uses `std::time::Instant`, so this
+requires `std`.
+
+```
+use std::time::Duration;
+
+use futures_enum::{AsyncRead, Future};
+
+struct Metrics;
+
+impl Metrics {
+    fn on_poll(
+        enum_name: &'static str,
+        variant: &'static str,
+        method: &'static str,
+        elapsed: Duration,
+        ready: bool,
+    ) {
+        // ...
+    }
+
+    fn on_bytes(enum_name: &'static str, variant: &'static str, method: &'static str, bytes: usize) {
+        // ...
+    }
+}
+
+#[derive(Future, AsyncRead)]
+#[futures_enum(observe = "Metrics")]
+enum Either<A, B> {
+    A(A),
+    B(B),
+}
+```
+
+## State machines
+
+`#[futures_enum(then = "path", output = "Type")]` makes `#[derive(Future)]`
+treat the variants as the states of a state machine. It generates an enum named
+`<Enum>Output` that has a variant holding the output of each variant. When the
+active variant completes, its output is passed to the function at `path`, which
+returns either [`ControlFlow::Break`][ControlFlow] with the output of the
+future, or `ControlFlow::Continue` with the next state, which then replaces the
+enum in place and is polled. The output of the future is `Type`, which must be
+the `B` of the `ControlFlow<B, Self>` that the function returns, and the
+variants may have different outputs. The function is called with the bounds of
+the enum, so bounds that it requires must be declared on the enum. The other
+derives on the enum ignore the option.
+
+```
+use std::{
+    future::{self, Future, Ready},
+    ops::ControlFlow,
+    pin::Pin,
+};
+
+use futures_enum::Future;
+
+#[derive(Future)]
+#[futures_enum(then = "Self::advance", output = "Option<String>")]
+enum Login {
+    Connect(Ready<Option<u16>>),
+    Greet(Pin<Box<dyn Future<Output = String>>>),
+}
+
+impl Login {
+    fn new(port: Option<u16>) -> Self {
+        Self::Connect(future::ready(port))
+    }
+
+    fn advance(output: LoginOutput) -> ControlFlow<Option<String>, Self> {
+        match output {
+            LoginOutput::Connect(None) => ControlFlow::Break(None),
+            LoginOutput::Connect(Some(port)) => {
+                let greet = async move { format!("hello from :{port}") };
+                ControlFlow::Continue(Self::Greet(Box::pin(greet)))
+            }
+            LoginOutput::Greet(greeting) => ControlFlow::Break(Some(greeting)),
+        }
+    }
+}
+```
+
+## Chaining streams
+
+`#[futures_enum(on_end = "path")]` makes `#[derive(Stream)]` call the function
+at `path` with `&self` when the active variant returns `None`. If the function
+returns `Some`, the returned value replaces the enum in place and is polled;
+the stream ends only when the function returns `None`. This is useful for
+paginated sources and fallbacks. The upper bound of `size_hint` is `None`,
+because the number of items of the following variants is unknown.
+`#[derive(AsyncIterator)]` does the same, and the other derives on the enum,
+such as `Sink`, ignore the option.
+
+```
+use futures::stream::{self, Iter};
+use futures_enum::Stream;
+
+#[derive(Stream)]
+#[futures_enum(on_end = "Self::next_page")]
+enum Pages {
+    First(Iter<std::ops::Range<u32>>),
+    Next(Iter<std::vec::IntoIter<u32>>),
+}
+
+impl Pages {
+    fn next_page(&self) -> Option<Self> {
+        match self {
+            Self::First(_) => Some(Self::Next(stream::iter(vec![10, 11]))),
+            Self::Next(_) => None,
+        }
+    }
+}
+```
+
+## Catching panics
+
+`#[futures_enum(catch_unwind = "path")]` makes `#[derive(Future)]` poll the
+active variant in [`catch_unwind`][catch_unwind], so that a panic in a variant
+is returned as a value instead of unwinding through the task. `Output` becomes
+`Result<Output, path>`, and a panic is converted with
+`path::new(variant_name, payload)`. This uses `std`.
+
+A stream that panicked must not be polled again, which needs state that the
+enum has no room for, so with `#[derive(Stream)]` the enum implements `Stream`
+as usual, and the option generates `<Enum>CatchUnwind`, a stream created by
+`<Enum>CatchUnwind::new(stream)` whose `Item` is `Result<Item, path>`. Like
+[`StreamExt::catch_unwind`][stream-catch-unwind], it ends after returning a
+panic. The other derives on the enum, such as `Sink`, ignore the option.
+
+```
+use std::{any::Any, future::Future};
+
+use futures_enum::Future;
+
+struct PluginPanic {
+    plugin: &'static str,
+    payload: Box<dyn Any + Send>,
+}
+
+impl PluginPanic {
+    fn new(plugin: &'static str, payload: Box<dyn Any + Send>) -> Self {
+        Self { plugin, payload }
+    }
+}
+
+#[derive(Future)]
+#[futures_enum(catch_unwind = "PluginPanic")]
+enum Plugin<A, B> {
+    A(A),
+    B(B),
+}
+
+async fn run<A: Future<Output = ()>, B: Future<Output = ()>>(plugin: Plugin<A, B>) {
+    if let Err(e) = plugin.await {
+        eprintln!("plugin `{}` panicked", e.plugin);
+    }
+}
+```
+
+## Yielding to the executor
+
+A stream that is always ready can keep a task that polls it in a loop from
+yielding, which starves the other tasks on the same executor thread.
+`#[futures_enum(budget = N)]` generates a wrapper that wakes the task and
+returns `Pending` once after `N` consecutive `Ready` results from `poll_next`,
+`poll_read`, `poll_read_vectored`, or `poll_fill_buf`. It does not depend on
+any runtime.
+
+The enum has no room for a counter, so the count is kept in the wrapper, and
+each value has its own budget. `#[derive(Stream)]` generates
+`<Enum>BudgetedStream`, and `#[derive(AsyncRead)]` generates
+`<Enum>BudgetedReader`, which also implements `AsyncBufRead` with
+`#[derive(AsyncBufRead)]`. The wrappers are created by `new` and implement the
+same traits as the enum. The other derives on the enum, such as `AsyncWrite`,
+ignore the option.
+
+```
+use futures_enum::{AsyncRead, AsyncWrite, Stream};
+
+#[derive(Stream, AsyncRead, AsyncWrite)]
+#[futures_enum(budget = 128)]
+enum Either<A, B> {
+    A(A),
+    B(B),
+}
+
+fn budgeted<A, B>(stream: Either<A, B>) -> EitherBudgetedStream<A, B> {
+    EitherBudgetedStream::new(stream)
+}
+```
+
+## Asserting `Send`, `Sync`, and `Unpin`
+
+`#[futures_enum(assert(Send, Sync, Unpin))]` checks that the type of each
+variant implements the listed traits, and reports an error at the variant that
+does not, instead of where the enum is used (for example, at `tokio::spawn`).
+Variants whose type refers to a type parameter of the enum can only be checked
+once the type arguments are known, so the generated impls require them to
+implement the listed traits, and using the enum with type arguments that do not
+reports an error that names the variant.
+
+```
+use std::{future::Future, pin::Pin};
+
+use futures_enum::Future;
+
+#[derive(Future)]
+#[futures_enum(assert(Send))]
+enum Task<'a> {
+    Boxed(Pin<Box<dyn Future<Output = ()> + Send + 'a>>),
+    Ready(std::future::Ready<()>),
+}
+```
+
+## Returning different futures or streams
+
+`#[dispatch]` on a function returning `impl Future`, `impl Stream`, etc. wraps
+each branch of the tail expression and each `return` in a variant of a hidden
+enum, and derives the traits named in the return type for it.
+
+```
+use std::future::Future;
+
+use futures_enum::dispatch;
+
+#[dispatch]
+fn foo(x: i32) -> impl Future<Output = i32> {
+    if x < 0 {
+        return async { 1 };
+    }
+    match x {
+        0 => async { 0 },
+        _ => async move { x },
+    }
+}
+```
+
+The traits recognized are the ones in the [supported traits](#supported-traits)
+list, except `IntoFuture`. As with the derives, all branches must have the same
+`Output`, `Item`, or `Error`. See also the [generated code](https://github.com/taiki-e/futures-enum/blob/HEAD/tests/expand/dispatch.expanded.rs).
+
+With `#[dispatch(into)]`, the branches can have different ones instead: each
+branch is converted with `Into` to the `Output` of `Future`, the `Item` of
+`Stream`, or the `Error` of `Sink` in the return type, which must be specified
+(as in `impl Future<Output = T>`). Other traits cannot be used with `into`.
+
+```
+use std::future::Future;
+
+use futures_enum::dispatch;
+
+#[dispatch(into)]
+fn foo(x: bool) -> impl Future<Output = i64> {
+    if x { async { 1_i32 } } else { async { 2_u8 } }
+}
+```
+
+See also the [generated code](https://github.com/taiki-e/futures-enum/blob/HEAD/tests/expand/dispatch_into.expanded.rs).
+
+## Delegating `#[async_trait]` traits
+
+`#[delegate_async_trait]` implements a trait written with [async-trait] for an
+enum by forwarding each method to the active variant. List the signatures of
+the methods to delegate after the path to the trait:
+
+```
+use async_trait::async_trait;
+use futures_enum::delegate_async_trait;
+
+#[async_trait]
+trait Storage {
+    async fn get(&self, key: &str) -> Vec<u8>;
+}
+
+struct Fs;
+struct Memory;
+
+#[async_trait]
+impl Storage for Fs {
+    async fn get(&self, key: &str) -> Vec<u8> {
+        unimplemented!()
+    }
+}
+
+#[async_trait]
+impl Storage for Memory {
+    async fn get(&self, key: &str) -> Vec<u8> {
+        unimplemented!()
+    }
+}
+
+#[delegate_async_trait(Storage {
+    async fn get(&self, key: &str) -> Vec<u8>;
+})]
+enum Backend {
+    Fs(Fs),
+    Memory(Memory),
+}
+```
+
+`async fn`s are awaited in the generated `async fn`, and `#[async_trait]` is
+applied to the generated impl, so the returned futures keep their `Send`
+bounds. Methods written in the desugared form (returning
+`Pin<Box<dyn Future + Send + '_>>`) are forwarded without boxing again. Use
+`#[delegate_async_trait(?Send, Trait { ... })]` for traits declared with
+`#[async_trait(?Send)]`.
 
 ## Related Projects
 
@@ -61,11 +532,20 @@ See [auto_enums] crate for how to automate patterns like this.
 - [io-enum]: \#\[derive(Read, Write, Seek, BufRead)\] for enums.
 - [iter-enum]: \#\[derive(Iterator, DoubleEndedIterator, ExactSizeIterator, Extend)\] for enums.
 
+[async-std]: https://github.com/async-rs/async-std
+[async-trait]: https://github.com/dtolnay/async-trait
 [auto_enums]: https://github.com/taiki-e/auto_enums
+[ControlFlow]: https://doc.rust-lang.org/std/ops/enum.ControlFlow.html
+[cargo-env]: https://doc.rust-lang.org/cargo/reference/config.html#env
+[catch_unwind]: https://doc.rust-lang.org/std/panic/fn.catch_unwind.html
 [derive_utils]: https://github.com/taiki-e/derive_utils
+[futures-lite]: https://github.com/smol-rs/futures-lite
 [io-enum]: https://github.com/taiki-e/io-enum
 [iter-enum]: https://github.com/taiki-e/iter-enum
 [proc-macro-derive]: https://doc.rust-lang.org/reference/procedural-macros.html#derive-macros
+[smol]: https://github.com/smol-rs/smol
+[stream-catch-unwind]: https://docs.rs/futures/0.3/futures/stream/trait.StreamExt.html#method.catch_unwind
+[tracing]: https://github.com/tokio-rs/tracing
 
 <!-- tidy:sync-markdown-to-rustdoc:end -->
 */
@@ -81,48 +561,247 @@ See [auto_enums] crate for how to automate patterns like this.
 ))]
 #![forbid(unsafe_code)]
 
-use derive_utils::{derive_trait, quick_derive};
-use proc_macro::TokenStream;
-use quote::{format_ident, quote};
-use syn::{Ident, parse_macro_input, parse_quote};
-
-fn default_crate_name() -> (Ident, Option<String>) {
-    (format_ident!("futures"), None)
-}
-
+mod args;
+mod assert;
+mod budget;
+mod chain;
+mod debug;
+mod delegate;
+mod dispatch;
+mod hygiene;
+mod instrument;
+mod join;
 #[cfg(feature = "renamed")]
-fn crate_name(crate_names: &[&str]) -> (Ident, Option<String>) {
-    use find_crate::Manifest;
+mod manifest;
+mod path;
+mod then;
+mod unwind;
+mod validate;
+mod wrapper;
+
+use derive_utils::{EnumData, EnumImpl};
+use proc_macro::TokenStream;
+use quote::{ToTokens as _, format_ident, quote, quote_spanned};
+use syn::{
+    DeriveInput, Fields, GenericParam, ItemEnum, ItemImpl, ItemTrait, Path, Result, TraitItem,
+    Type, WherePredicate, parse_macro_input, parse_quote, spanned::Spanned as _,
+};
 
-    let Some(manifest) = Manifest::new().ok() else { return default_crate_name() };
+use crate::{
+    args::Args,
+    path::{Module, module_path, trait_path},
+};
 
-    manifest
-        .find2(|name, version| {
-            if name == "futures" {
-                version == "*" || version == "0.3" || version.starts_with("0.3.")
-            } else {
-                crate_names.contains(&name)
+fn expand(
+    input: TokenStream,
+    derive: &str,
+    f: impl FnOnce(&EnumData, &Args) -> Result<proc_macro2::TokenStream>,
+) -> TokenStream {
+    let input: DeriveInput = parse_macro_input!(input);
+    validate::validate(&input, derive)
+        .and_then(|()| syn::parse2(input.into_token_stream()))
+        .and_then(|data: EnumData| {
+            let args = Args::new(&data.attrs)?;
+            if let Some(join) = &args.join {
+                return Err(syn::Error::new_spanned(
+                    join,
+                    "`join` option may only be used with `#[derive(IntoFuture)]` on structs",
+                ));
             }
+            let mut tokens = f(&data, &args)?;
+            tokens.extend(assert::auto_traits(&data, args.assert.as_deref().unwrap_or_default()));
+            debug::dump(derive, &data.ident, &tokens, args.debug.is_some());
+            Ok(tokens)
         })
-        .map_or_else(default_crate_name, |package| {
-            if package.is_original() {
-                (format_ident!("{}", package.name), None)
+        .unwrap_or_else(|e| e.to_compile_error())
+        .into()
+}
+
+// Implements `trait_def` for the enum, like `derive_utils::derive_trait`.
+//
+// If the trait has associated types, this also requires each variant to have
+// the same associated types as the first variant through a marker trait, so
+// that mismatches are reported with the names of the variants. The marker
+// trait has the equality as a supertrait bound and replaces the one that
+// `derive_utils` adds to the where clause, which would otherwise be reported
+// first. It is required by the impl, where it is checked when the impl is
+// used, or if the types of the variants do not depend on generic parameters,
+// checked here.
+fn derive(
+    data: &EnumData,
+    args: &Args,
+    path: Path,
+    mut trait_def: ItemTrait,
+) -> proc_macro2::TokenStream {
+    hygiene::rename_generics(&mut trait_def, &data.generics);
+    let assoc_types: Vec<_> = trait_def
+        .items
+        .iter()
+        .filter_map(|item| match item {
+            TraitItem::Type(ty) => Some(ty.ident.clone()),
+            _ => None,
+        })
+        .collect();
+    let params: Vec<_> = trait_def
+        .generics
+        .type_params()
+        .map(|param| {
+            let ident = &param.ident;
+            quote!(#ident,)
+        })
+        .collect();
+    let has_generics = !params.is_empty();
+    let trait_generics = trait_def.generics.split_for_impl().1;
+    let trait_ = quote!(#path #trait_generics);
+    let trait_path = path.to_token_stream();
+    let mut impl_ = EnumImpl::from_trait(data, path, None, trait_def);
+    if assoc_types.is_empty() || data.variants.len() < 2 {
+        return build(impl_, data, args);
+    }
+
+    let mut asserts = vec![];
+    let first_variant = &data.variants[0].ident;
+    let mut field_types = data.field_types();
+    let first = field_types.next().unwrap();
+    for (i, (v, ty)) in data.variants.iter().skip(1).zip(field_types).enumerate() {
+        let concrete = !has_generics && is_concrete(first, data) && is_concrete(ty, data);
+        for assoc in &assoc_types {
+            let assert = format_ident!("__Assert{}{}", assoc, i + 1);
+            let message = format!(
+                "variant `{}` has `{assoc} = {{U}}`, but variant `{first_variant}` has \
+                 `{assoc} = {{T}}`",
+                v.ident
+            );
+            let label = format!("expected `{assoc} = {{T}}`");
+            let note = format!("all variants must have the same `{assoc}`");
+            // `do_not_recommend` makes rustc report the marker trait instead
+            // of the equality required by its impl.
+            asserts.push(quote! {
+                #[diagnostic::on_unimplemented(message = #message, label = #label, note = #note)]
+                trait #assert<#(#params)* T: ?::core::marker::Sized, U: ?::core::marker::Sized>:
+                    #trait_path<#(#params)* #assoc = T>
+                {
+                }
+                #[diagnostic::do_not_recommend]
+                impl<F, #(#params)* T: ?::core::marker::Sized> #assert<#(#params)* T, T> for F
+                where
+                    F: ?::core::marker::Sized + #trait_path<#(#params)* #assoc = T>,
+                {
+                }
+            });
+            let expected = quote!(<#first as #trait_>::#assoc);
+            if concrete {
+                let call = quote_spanned!(ty.span()=> assert::<#ty>());
+                asserts.push(quote! {
+                    let _ = || {
+                        fn assert<T>()
+                        where
+                            T: ?::core::marker::Sized + #trait_,
+                            T: #assert<#expected, <T as #trait_>::#assoc>,
+                        {
+                        }
+                        #call;
+                    };
+                });
+                // Without a bound variable, this is rejected as a trivially
+                // false bound in addition to the error above.
+                let lifetime = hygiene::fresh_lifetime("__a", &data.generics);
+                impl_.push_where_predicate(parse_quote! {
+                    for<#lifetime> #ty: #assert<#expected, <#ty as #trait_>::#assoc>
+                });
             } else {
-                (format_ident!("{}", &package.name), Some(package.original_name().to_owned()))
+                impl_.push_where_predicate(parse_quote! {
+                    #ty: #assert<#(#params)* #expected, <#ty as #trait_>::#assoc>
+                });
             }
-        })
+        }
+    }
+    // Replace `Variant: Trait<Assoc = <First as Trait>::Assoc>` that
+    // `derive_utils` adds after the bounds of the enum and the first variant.
+    let mut item = impl_.build_impl();
+    let skip = data.generics.where_clause.as_ref().map_or(0, |w| w.predicates.len()) + 1;
+    let predicates = &mut item.generics.where_clause.as_mut().unwrap().predicates;
+    for (predicate, ty) in predicates.iter_mut().skip(skip).zip(data.field_types().skip(1)) {
+        *predicate = parse_quote!(#ty: #trait_);
+    }
+    let impl_ = finish(item, data, args);
+    quote! {
+        const _: () = {
+            #(#asserts)*
+            #impl_
+        };
+    }
 }
 
-#[cfg(not(feature = "renamed"))]
-fn crate_name(_: &[&str]) -> (Ident, Option<String>) {
-    default_crate_name()
+fn build(impl_: EnumImpl<'_>, data: &EnumData, args: &Args) -> proc_macro2::TokenStream {
+    finish(impl_.build_impl(), data, args)
 }
 
-#[proc_macro_derive(Future)]
-pub fn derive_future(input: TokenStream) -> TokenStream {
-    quick_derive! {
-        input,
-        ::core::future::Future,
+// Applies the options that change the methods of the generated impl.
+fn finish(mut item: ItemImpl, data: &EnumData, args: &Args) -> proc_macro2::TokenStream {
+    if let Some(on_end) = &args.on_end {
+        chain::on_end(&mut item, data, on_end);
+    }
+    if let Some(panic) = &args.catch_unwind {
+        unwind::catch_unwind(&mut item, data, panic);
+    }
+    instrument::instrument(&mut item, data, args);
+    let markers = assert::bounds(&mut item, data, args.assert.as_deref().unwrap_or_default());
+    if markers.is_empty() {
+        item.into_token_stream()
+    } else {
+        quote! {
+            const _: () = {
+                #markers
+                #item
+            };
+        }
+    }
+}
+
+/// Mismatched associated types are reported only with the names of the
+/// variants, not as a type mismatch on the impl.
+///
+/// ```compile_fail,E0277
+/// // error[E0277]: variant `B` has `Output = u16`, but variant `A` has `Output = u8`
+/// #[derive(futures_enum::Future)]
+/// enum Enum {
+///     A(std::future::Ready<u8>),
+///     B(std::future::Ready<u16>),
+/// }
+/// ```
+///
+/// ```compile_fail,E0277
+/// // error[E0277]: variant `B` has `Output = u16`, but variant `A` has `Output = u8`
+/// #[derive(futures_enum::Future)]
+/// enum Enum<B> {
+///     A(std::future::Ready<u8>),
+///     B(B),
+/// }
+/// fn f() -> impl std::future::Future {
+///     Enum::<std::future::Ready<u16>>::B(std::future::ready(1))
+/// }
+/// ```
+#[cfg(doctest)]
+mod assoc_type_mismatch {}
+
+// Returns `true` if `ty` does not refer to the generic parameters of the enum.
+fn is_concrete(ty: &Type, data: &EnumData) -> bool {
+    let params: Vec<_> = data
+        .generics
+        .params
+        .iter()
+        .map(|param| match param {
+            GenericParam::Type(param) => param.ident.to_string(),
+            GenericParam::Lifetime(param) => param.lifetime.ident.to_string(),
+            GenericParam::Const(param) => param.ident.to_string(),
+        })
+        .collect();
+    !assert::refers_to(ty, &params)
+}
+
+fn future_trait() -> ItemTrait {
+    parse_quote! {
         trait Future {
             type Output;
             #[inline]
@@ -134,78 +813,167 @@ pub fn derive_future(input: TokenStream) -> TokenStream {
     }
 }
 
-#[proc_macro_derive(Stream)]
-pub fn derive_stream(input: TokenStream) -> TokenStream {
-    let (crate_, _) = crate_name(&["futures", "futures-util", "futures-core"]);
-
-    derive_trait(
-        &parse_macro_input!(input),
-        parse_quote!(::#crate_::stream::Stream),
-        None,
-        parse_quote! {
-            trait Stream {
-                type Item;
-                #[inline]
-                fn poll_next(
-                    self: ::core::pin::Pin<&mut Self>,
-                    cx: &mut ::core::task::Context<'_>,
-                ) -> ::core::task::Poll<::core::option::Option<Self::Item>>;
+#[proc_macro_derive(Future, attributes(futures_enum))]
+pub fn derive_future(input: TokenStream) -> TokenStream {
+    expand(input, "Future", |data, args| {
+        if let (Some(then), Some(output)) = (&args.then, &args.output) {
+            return Ok(then::derive(data, args, then, output));
+        }
+        Ok(derive(data, args, parse_quote!(::core::future::Future), future_trait()))
+    })
+}
+
+#[proc_macro_derive(IntoFuture, attributes(futures_enum))]
+pub fn derive_into_future(input: TokenStream) -> TokenStream {
+    if let Some(tokens) = join::expand(&input) {
+        return tokens.into();
+    }
+    expand(input, "IntoFuture", |data, args| {
+        let ident = &data.ident;
+        let future = format_ident!("{}IntoFuture", ident);
+
+        // The companion enum holds the `IntoFuture::IntoFuture` of each variant and
+        // implements `Future` in the same way as `#[derive(Future)]`.
+        let mut generics = data.generics.clone();
+        generics.make_where_clause().predicates.extend(
+            data.field_types()
+                .map(|ty| -> WherePredicate { parse_quote!(#ty: ::core::future::IntoFuture) }),
+        );
+        let mut item = ItemEnum::clone(data);
+        let doc = format!(" The `IntoFuture::IntoFuture` type of [`{ident}`].");
+        item.attrs = vec![parse_quote!(#[doc = #doc])];
+        item.ident = future.clone();
+        item.generics = generics.clone();
+        for (v, ty) in item.variants.iter_mut().zip(data.field_types()) {
+            v.fields =
+                Fields::Unnamed(parse_quote!((<#ty as ::core::future::IntoFuture>::IntoFuture)));
+        }
+        let companion: EnumData = parse_quote!(#item);
+        let future_impl =
+            derive(&companion, args, parse_quote!(::core::future::Future), future_trait());
+
+        let ty_generics = data.generics.split_for_impl().1;
+        generics
+            .make_where_clause()
+            .predicates
+            .push(parse_quote!(#future #ty_generics: ::core::future::Future));
+        let (impl_generics, _, where_clause) = generics.split_for_impl();
+        let variants = data.variant_idents();
+        Ok(quote! {
+            #companion
+            #future_impl
+            impl #impl_generics ::core::future::IntoFuture for #ident #ty_generics #where_clause {
+                type Output = <#future #ty_generics as ::core::future::Future>::Output;
+                type IntoFuture = #future #ty_generics;
                 #[inline]
-                fn size_hint(&self) -> (usize, ::core::option::Option<usize>);
+                fn into_future(self) -> Self::IntoFuture {
+                    match self {
+                        #(#ident::#variants(x) => {
+                            #future::#variants(::core::future::IntoFuture::into_future(x))
+                        })*
+                    }
+                }
             }
-        },
-    )
-    .into()
+        })
+    })
 }
 
-#[proc_macro_derive(Sink)]
-pub fn derive_sink(input: TokenStream) -> TokenStream {
-    let (crate_, original) = crate_name(&["futures", "futures-sink"]);
-    let path = if original.as_deref() == Some("futures-sink") {
-        quote!(::#crate_)
-    } else {
-        quote!(::#crate_::sink)
-    };
-
-    derive_trait(&parse_macro_input!(input), parse_quote!(#path::Sink), None, parse_quote! {
-        trait Sink<__Item> {
-            type Error;
-            #[inline]
-            fn poll_ready(
-                self: ::core::pin::Pin<&mut Self>,
-                cx: &mut ::core::task::Context<'_>,
-            ) -> ::core::task::Poll<::core::result::Result<(), Self::Error>>;
-            #[inline]
-            fn start_send(
-                self: ::core::pin::Pin<&mut Self>,
-                item: __Item,
-            ) -> ::core::result::Result<(), Self::Error>;
+fn stream_trait() -> ItemTrait {
+    parse_quote! {
+        trait Stream {
+            type Item;
             #[inline]
-            fn poll_flush(
+            fn poll_next(
                 self: ::core::pin::Pin<&mut Self>,
                 cx: &mut ::core::task::Context<'_>,
-            ) -> ::core::task::Poll<::core::result::Result<(), Self::Error>>;
+            ) -> ::core::task::Poll<::core::option::Option<Self::Item>>;
             #[inline]
-            fn poll_close(
-                self: ::core::pin::Pin<&mut Self>,
-                cx: &mut ::core::task::Context<'_>,
-            ) -> ::core::task::Poll<::core::result::Result<(), Self::Error>>;
+            fn size_hint(&self) -> (usize, ::core::option::Option<usize>);
         }
+    }
+}
+
+#[proc_macro_derive(Stream, attributes(futures_enum))]
+pub fn derive_stream(input: TokenStream) -> TokenStream {
+    expand(input, "Stream", |data, args| {
+        let path = trait_path(args, Module::Stream, "Stream")?;
+        let path: Path = parse_quote!(#path);
+        let mut tokens = derive(data, args, path.clone(), stream_trait());
+        if let Some(panic) = &args.catch_unwind {
+            tokens.extend(unwind::stream(data, &path, &stream_trait(), panic));
+        }
+        if let Some(n) = &args.budget {
+            let ident = budget::stream_ident(data);
+            tokens.extend(budget::define(data, &ident, "stream"));
+            tokens.extend(
+                budget::budget(data, &ident, &path, &stream_trait(), n).into_token_stream(),
+            );
+        }
+        Ok(tokens)
     })
-    .into()
 }
 
-#[proc_macro_derive(AsyncRead)]
-pub fn derive_async_read(input: TokenStream) -> TokenStream {
-    let (crate_, original) = crate_name(&["futures", "futures-io"]);
+// `AsyncIterator` has the same methods as `Stream`.
+#[cfg(feature = "async-iterator")]
+#[proc_macro_derive(AsyncIterator, attributes(futures_enum))]
+pub fn derive_async_iterator(input: TokenStream) -> TokenStream {
+    expand(input, "AsyncIterator", |data, args| {
+        Ok(derive(data, args, parse_quote!(::core::async_iter::AsyncIterator), stream_trait()))
+    })
+}
 
-    let path = if original.as_deref() == Some("futures-io") {
-        quote!(::#crate_)
-    } else {
-        quote!(::#crate_::io)
-    };
+#[proc_macro_derive(Sink, attributes(futures_enum))]
+pub fn derive_sink(input: TokenStream) -> TokenStream {
+    expand(input, "Sink", |data, args| {
+        let path = trait_path(args, Module::Sink, "Sink")?;
+        Ok(derive(data, args, parse_quote!(#path), parse_quote! {
+            trait Sink<__Item> {
+                type Error;
+                #[inline]
+                fn poll_ready(
+                    self: ::core::pin::Pin<&mut Self>,
+                    cx: &mut ::core::task::Context<'_>,
+                ) -> ::core::task::Poll<::core::result::Result<(), Self::Error>>;
+                #[inline]
+                fn start_send(
+                    self: ::core::pin::Pin<&mut Self>,
+                    item: __Item,
+                ) -> ::core::result::Result<(), Self::Error>;
+                #[inline]
+                fn poll_flush(
+                    self: ::core::pin::Pin<&mut Self>,
+                    cx: &mut ::core::task::Context<'_>,
+                ) -> ::core::task::Poll<::core::result::Result<(), Self::Error>>;
+                #[inline]
+                fn poll_close(
+                    self: ::core::pin::Pin<&mut Self>,
+                    cx: &mut ::core::task::Context<'_>,
+                ) -> ::core::task::Poll<::core::result::Result<(), Self::Error>>;
+            }
+        }))
+    })
+}
 
-    derive_trait(&parse_macro_input!(input), parse_quote!(#path::AsyncRead), None, parse_quote! {
+#[cfg(feature = "coroutine")]
+#[proc_macro_derive(Coroutine, attributes(futures_enum))]
+pub fn derive_coroutine(input: TokenStream) -> TokenStream {
+    expand(input, "Coroutine", |data, args| {
+        Ok(derive(data, args, parse_quote!(::core::ops::Coroutine), parse_quote! {
+            trait Coroutine<__R> {
+                type Yield;
+                type Return;
+                #[inline]
+                fn resume(
+                    self: ::core::pin::Pin<&mut Self>,
+                    arg: __R,
+                ) -> ::core::ops::CoroutineState<Self::Yield, Self::Return>;
+            }
+        }))
+    })
+}
+
+fn async_read_trait() -> ItemTrait {
+    parse_quote! {
         trait AsyncRead {
             #[inline]
             fn poll_read(
@@ -220,97 +988,199 @@ pub fn derive_async_read(input: TokenStream) -> TokenStream {
                 bufs: &mut [::std::io::IoSliceMut<'_>],
             ) -> ::core::task::Poll<::std::io::Result<usize>>;
         }
+    }
+}
+
+#[proc_macro_derive(AsyncRead, attributes(futures_enum))]
+pub fn derive_async_read(input: TokenStream) -> TokenStream {
+    expand(input, "AsyncRead", |data, args| {
+        let path = trait_path(args, Module::Io, "AsyncRead")?;
+        let path: Path = parse_quote!(#path);
+        let mut tokens = derive(data, args, path.clone(), async_read_trait());
+        if let Some(n) = &args.budget {
+            let ident = budget::reader_ident(data);
+            tokens.extend(budget::define(data, &ident, "reader"));
+            tokens.extend(
+                budget::budget(data, &ident, &path, &async_read_trait(), n).into_token_stream(),
+            );
+        }
+        Ok(tokens)
     })
-    .into()
 }
 
-#[proc_macro_derive(AsyncWrite)]
+#[proc_macro_derive(AsyncWrite, attributes(futures_enum))]
 pub fn derive_async_write(input: TokenStream) -> TokenStream {
-    let (crate_, original) = crate_name(&["futures", "futures-io"]);
+    expand(input, "AsyncWrite", |data, args| {
+        let path = trait_path(args, Module::Io, "AsyncWrite")?;
+        Ok(derive(data, args, parse_quote!(#path), parse_quote! {
+            trait AsyncWrite {
+                #[inline]
+                fn poll_write(
+                    self: ::core::pin::Pin<&mut Self>,
+                    cx: &mut ::core::task::Context<'_>,
+                    buf: &[u8],
+                ) -> ::core::task::Poll<::std::io::Result<usize>>;
+                #[inline]
+                fn poll_write_vectored(
+                    self: ::core::pin::Pin<&mut Self>,
+                    cx: &mut ::core::task::Context<'_>,
+                    bufs: &[::std::io::IoSlice<'_>],
+                ) -> ::core::task::Poll<::std::io::Result<usize>>;
+                #[inline]
+                fn poll_flush(
+                    self: ::core::pin::Pin<&mut Self>,
+                    cx: &mut ::core::task::Context<'_>,
+                ) -> ::core::task::Poll<::std::io::Result<()>>;
+                #[inline]
+                fn poll_close(
+                    self: ::core::pin::Pin<&mut Self>,
+                    cx: &mut ::core::task::Context<'_>,
+                ) -> ::core::task::Poll<::std::io::Result<()>>;
+            }
+        }))
+    })
+}
 
-    let path = if original.as_deref() == Some("futures-io") {
-        quote!(::#crate_)
-    } else {
-        quote!(::#crate_::io)
-    };
+#[proc_macro_derive(AsyncSeek, attributes(futures_enum))]
+pub fn derive_async_seek(input: TokenStream) -> TokenStream {
+    expand(input, "AsyncSeek", |data, args| {
+        let path = trait_path(args, Module::Io, "AsyncSeek")?;
+        Ok(derive(data, args, parse_quote!(#path), parse_quote! {
+            trait AsyncSeek {
+                #[inline]
+                fn poll_seek(
+                    self: ::core::pin::Pin<&mut Self>,
+                    cx: &mut ::core::task::Context<'_>,
+                    pos: ::std::io::SeekFrom,
+                ) -> ::core::task::Poll<::std::io::Result<u64>>;
+            }
+        }))
+    })
+}
 
-    derive_trait(&parse_macro_input!(input), parse_quote!(#path::AsyncWrite), None, parse_quote! {
-        trait AsyncWrite {
-            #[inline]
-            fn poll_write(
-                self: ::core::pin::Pin<&mut Self>,
-                cx: &mut ::core::task::Context<'_>,
-                buf: &[u8],
-            ) -> ::core::task::Poll<::std::io::Result<usize>>;
+fn async_buf_read_trait() -> ItemTrait {
+    parse_quote! {
+        trait AsyncBufRead {
             #[inline]
-            fn poll_write_vectored(
-                self: ::core::pin::Pin<&mut Self>,
-                cx: &mut ::core::task::Context<'_>,
-                bufs: &[::std::io::IoSlice<'_>],
-            ) -> ::core::task::Poll<::std::io::Result<usize>>;
-            #[inline]
-            fn poll_flush(
-                self: ::core::pin::Pin<&mut Self>,
+            fn poll_fill_buf<'__a>(
+                self: ::core::pin::Pin<&'__a mut Self>,
                 cx: &mut ::core::task::Context<'_>,
-            ) -> ::core::task::Poll<::std::io::Result<()>>;
+            ) -> ::core::task::Poll<::std::io::Result<&'__a [u8]>>;
             #[inline]
-            fn poll_close(
-                self: ::core::pin::Pin<&mut Self>,
-                cx: &mut ::core::task::Context<'_>,
-            ) -> ::core::task::Poll<::std::io::Result<()>>;
+            fn consume(self: ::core::pin::Pin<&mut Self>, amt: usize);
         }
-    })
-    .into()
+    }
 }
 
-#[proc_macro_derive(AsyncSeek)]
-pub fn derive_async_seek(input: TokenStream) -> TokenStream {
-    let (crate_, original) = crate_name(&["futures", "futures-io"]);
-
-    let path = if original.as_deref() == Some("futures-io") {
-        quote!(::#crate_)
-    } else {
-        quote!(::#crate_::io)
-    };
-
-    derive_trait(&parse_macro_input!(input), parse_quote!(#path::AsyncSeek), None, parse_quote! {
-        trait AsyncSeek {
-            #[inline]
-            fn poll_seek(
-                self: ::core::pin::Pin<&mut Self>,
-                cx: &mut ::core::task::Context<'_>,
-                pos: ::std::io::SeekFrom,
-            ) -> ::core::task::Poll<::std::io::Result<u64>>;
+#[proc_macro_derive(AsyncBufRead, attributes(futures_enum))]
+pub fn derive_async_buf_read(input: TokenStream) -> TokenStream {
+    expand(input, "AsyncBufRead", |data, args| {
+        let path = trait_path(args, Module::Io, "AsyncBufRead")?;
+        let path: Path = parse_quote!(#path);
+        let mut tokens = derive(data, args, path.clone(), async_buf_read_trait());
+        // The wrapper is defined by `#[derive(AsyncRead)]`, as `AsyncRead` is
+        // a supertrait of `AsyncBufRead`.
+        if let Some(n) = &args.budget {
+            let ident = budget::reader_ident(data);
+            tokens.extend(
+                budget::budget(data, &ident, &path, &async_buf_read_trait(), n).into_token_stream(),
+            );
         }
+        Ok(tokens)
     })
-    .into()
 }
 
-#[proc_macro_derive(AsyncBufRead)]
-pub fn derive_async_buf_read(input: TokenStream) -> TokenStream {
-    let (crate_, original) = crate_name(&["futures", "futures-io"]);
+#[proc_macro_derive(Spawn, attributes(futures_enum))]
+pub fn derive_spawn(input: TokenStream) -> TokenStream {
+    expand(input, "Spawn", |data, args| {
+        let path = module_path(args, Module::Task)?;
+        Ok(derive(data, args, parse_quote!(#path::Spawn), parse_quote! {
+            trait Spawn {
+                #[inline]
+                fn spawn_obj(
+                    &self,
+                    future: #path::FutureObj<'static, ()>,
+                ) -> ::core::result::Result<(), #path::SpawnError>;
+                #[inline]
+                fn status(&self) -> ::core::result::Result<(), #path::SpawnError>;
+            }
+        }))
+    })
+}
 
-    let path = if original.as_deref() == Some("futures-io") {
-        quote!(::#crate_)
-    } else {
-        quote!(::#crate_::io)
-    };
-
-    derive_trait(
-        &parse_macro_input!(input),
-        parse_quote!(#path::AsyncBufRead),
-        None,
-        parse_quote! {
-            trait AsyncBufRead {
+#[proc_macro_derive(LocalSpawn, attributes(futures_enum))]
+pub fn derive_local_spawn(input: TokenStream) -> TokenStream {
+    expand(input, "LocalSpawn", |data, args| {
+        let path = module_path(args, Module::Task)?;
+        Ok(derive(data, args, parse_quote!(#path::LocalSpawn), parse_quote! {
+            trait LocalSpawn {
                 #[inline]
-                fn poll_fill_buf<'__a>(
-                    self: ::core::pin::Pin<&'__a mut Self>,
-                    cx: &mut ::core::task::Context<'_>,
-                ) -> ::core::task::Poll<::std::io::Result<&'__a [u8]>>;
+                fn spawn_local_obj(
+                    &self,
+                    future: #path::LocalFutureObj<'static, ()>,
+                ) -> ::core::result::Result<(), #path::SpawnError>;
                 #[inline]
-                fn consume(self: ::core::pin::Pin<&mut Self>, amt: usize);
+                fn status_local(&self) -> ::core::result::Result<(), #path::SpawnError>;
             }
-        },
-    )
-    .into()
+        }))
+    })
+}
+
+#[proc_macro_derive(VariantName, attributes(futures_enum))]
+pub fn derive_variant_name(input: TokenStream) -> TokenStream {
+    expand(input, "VariantName", |data, _| {
+        let ident = &data.ident;
+        let vis = &data.vis;
+        let (impl_generics, ty_generics, where_clause) = data.generics.split_for_impl();
+        let variants: Vec<_> = data.variant_idents().collect();
+        let names = variants.iter().map(ToString::to_string);
+        let indices = 0..variants.len();
+        Ok(quote! {
+            impl #impl_generics #ident #ty_generics #where_clause {
+                /// Returns the name of the active variant.
+                #[inline]
+                #vis fn variant_name(&self) -> &'static str {
+                    match self {
+                        #(#ident::#variants(_) => #names,)*
+                    }
+                }
+                /// Returns the index of the active variant, in declaration order.
+                #[inline]
+                #vis fn variant_index(&self) -> usize {
+                    match self {
+                        #(#ident::#variants(_) => #indices,)*
+                    }
+                }
+            }
+        })
+    })
+}
+
+#[proc_macro_derive(OpaqueDebug, attributes(futures_enum))]
+pub fn derive_opaque_debug(input: TokenStream) -> TokenStream {
+    expand(input, "OpaqueDebug", |data, _| {
+        let ident = &data.ident;
+        let (impl_generics, ty_generics, where_clause) = data.generics.split_for_impl();
+        let variants: Vec<_> = data.variant_idents().collect();
+        let names = variants.iter().map(|v| format!("{ident}::{v}(..)"));
+        Ok(quote! {
+            impl #impl_generics ::core::fmt::Debug for #ident #ty_generics #where_clause {
+                fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
+                    f.write_str(match self {
+                        #(#ident::#variants(_) => #names,)*
+                    })
+                }
+            }
+        })
+    })
+}
+
+#[proc_macro_attribute]
+pub fn delegate_async_trait(args: TokenStream, input: TokenStream) -> TokenStream {
+    delegate::attribute(args.into(), input.into()).into()
+}
+
+#[proc_macro_attribute]
+pub fn dispatch(args: TokenStream, input: TokenStream) -> TokenStream {
+    dispatch::attribute(args.into(), input.into()).into()
 }
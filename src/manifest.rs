@@ -0,0 +1,448 @@
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+// Reads the dependencies of the crate being compiled from its Cargo.toml.
+
+use std::{
+    collections::BTreeMap,
+    env, fs,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex, PoisonError},
+    time::SystemTime,
+};
+
+use toml::{Value, value::Table};
+
+// The dependency tables that are searched, in order of precedence.
+const DEPENDENCY_TABLES: &[&str] = &["dependencies", "dev-dependencies"];
+
+// Each derive reads the manifest, so the result is cached per manifest path
+// for the lifetime of the proc-macro process.
+static CACHE: Mutex<BTreeMap<PathBuf, Arc<Manifest>>> = Mutex::new(BTreeMap::new());
+
+pub(crate) struct Dependency {
+    // The name of the crate in the source code.
+    pub(crate) name: String,
+    // The original package name.
+    pub(crate) package: String,
+    // The version requirement, or `*` if not specified.
+    pub(crate) version: String,
+}
+
+pub(crate) struct Manifest {
+    dependencies: Vec<Dependency>,
+    // The files this manifest was read from and their modification times.
+    files: Vec<(PathBuf, Option<SystemTime>)>,
+}
+
+impl Manifest {
+    // Returns the manifest in `CARGO_MANIFEST_DIR`, reading it again only if
+    // it or the workspace root manifest has been modified since the last call.
+    pub(crate) fn new() -> Result<Arc<Self>, String> {
+        let dir = env::var_os("CARGO_MANIFEST_DIR")
+            .ok_or("`CARGO_MANIFEST_DIR` environment variable is not set")?;
+        Self::cached(Path::new(&dir).join("Cargo.toml"))
+    }
+
+    // Returns the manifest at `path` from the cache, or reads it if it is not
+    // cached or any of the files it was read from has been modified.
+    fn cached(path: PathBuf) -> Result<Arc<Self>, String> {
+        let mut cache = CACHE.lock().unwrap_or_else(PoisonError::into_inner);
+        if let Some(manifest) = cache.get(&path) {
+            if manifest.files.iter().all(|(path, time)| time.is_some() && mtime(path) == *time) {
+                return Ok(Arc::clone(manifest));
+            }
+        }
+        let manifest = Arc::new(Self::load(&path)?);
+        cache.insert(path, Arc::clone(&manifest));
+        Ok(manifest)
+    }
+
+    // Reads the manifest at `path`, resolving dependencies inherited from the
+    // workspace.
+    fn load(path: &Path) -> Result<Self, String> {
+        let mut files = vec![(path.to_owned(), mtime(path))];
+        let manifest = read(path)?;
+
+        let mut entries = vec![];
+        for table in DEPENDENCY_TABLES {
+            if let Some(table) = manifest.get(*table).and_then(Value::as_table) {
+                entries.extend(table);
+            }
+        }
+        // Target-specific dependencies have lower precedence than the others.
+        if let Some(targets) = manifest.get("target").and_then(Value::as_table) {
+            for table in DEPENDENCY_TABLES {
+                for target in targets.values() {
+                    if let Some(table) = target.get(*table).and_then(Value::as_table) {
+                        entries.extend(table);
+                    }
+                }
+            }
+        }
+
+        let mut workspace = None;
+        let mut dependencies = Vec::with_capacity(entries.len());
+        for (key, mut value) in entries {
+            if value.get("workspace").and_then(Value::as_bool) == Some(true) {
+                if workspace.is_none() {
+                    workspace = Some(workspace_dependencies(path, &manifest, &mut files)?);
+                }
+                value = workspace.as_ref().unwrap().get(key).ok_or_else(|| {
+                    format!(
+                        "`{key}` is not found in `workspace.dependencies` of the workspace root"
+                    )
+                })?;
+            }
+            dependencies.push(Dependency {
+                name: key.replace('-', "_"),
+                package: value
+                    .get("package")
+                    .and_then(Value::as_str)
+                    .map_or_else(|| key.clone(), str::to_owned),
+                version: value
+                    .as_str()
+                    .or_else(|| value.get("version")?.as_str())
+                    .unwrap_or("*")
+                    .to_owned(),
+            });
+        }
+        Ok(Self { dependencies, files })
+    }
+
+    // Returns a manifest with the given (name, package, version) dependencies.
+    #[cfg(test)]
+    pub(crate) fn from_dependencies(dependencies: &[(&str, &str, &str)]) -> Self {
+        let dependencies = dependencies
+            .iter()
+            .map(|&(name, package, version)| Dependency {
+                name: name.to_owned(),
+                package: package.to_owned(),
+                version: version.to_owned(),
+            })
+            .collect();
+        Self { dependencies, files: vec![] }
+    }
+
+    pub(crate) fn find(&self, predicate: impl FnMut(&&Dependency) -> bool) -> Option<&Dependency> {
+        self.dependencies.iter().find(predicate)
+    }
+}
+
+fn mtime(path: &Path) -> Option<SystemTime> {
+    fs::metadata(path).and_then(|m| m.modified()).ok()
+}
+
+fn read(path: &Path) -> Result<Table, String> {
+    let s = fs::read_to_string(path)
+        .map_err(|e| format!("failed to read `{}`: {e}", path.display()))?;
+    toml::from_str(&s).map_err(|e| format!("failed to parse `{}`: {e}", path.display()))
+}
+
+// Returns the `workspace.dependencies` table of the workspace that the
+// manifest at `path` belongs to.
+fn workspace_dependencies(
+    path: &Path,
+    manifest: &Table,
+    files: &mut Vec<(PathBuf, Option<SystemTime>)>,
+) -> Result<Table, String> {
+    let dir = path.parent().unwrap();
+    let root = match manifest.get("package").and_then(|p| p.get("workspace")?.as_str()) {
+        Some(root) => Some(dir.join(root).join("Cargo.toml")),
+        None => find_workspace_root(dir)?,
+    };
+    let Some(root) = root else {
+        return Err(format!("failed to find the workspace root of `{}`", path.display()));
+    };
+    let mut root = if root == path {
+        manifest.clone()
+    } else {
+        files.push((root.clone(), mtime(&root)));
+        read(&root)?
+    };
+    Ok(match root.remove("workspace") {
+        Some(Value::Table(mut workspace)) => match workspace.remove("dependencies") {
+            Some(Value::Table(dependencies)) => dependencies,
+            _ => Table::new(),
+        },
+        _ => Table::new(),
+    })
+}
+
+// Returns the path to the nearest manifest that has a `[workspace]` table,
+// starting from `dir` itself.
+fn find_workspace_root(dir: &Path) -> Result<Option<PathBuf>, String> {
+    for dir in dir.ancestors() {
+        let path = dir.join("Cargo.toml");
+        if path.is_file() && read(&path)?.contains_key("workspace") {
+            return Ok(Some(path));
+        }
+    }
+    Ok(None)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        fs,
+        path::{Path, PathBuf},
+        sync::{
+            Arc,
+            atomic::{AtomicUsize, Ordering},
+        },
+        time::{Duration, SystemTime},
+    };
+
+    use super::Manifest;
+
+    // Creates an empty temporary directory that is unique to this call.
+    fn temp_dir() -> PathBuf {
+        static COUNT: AtomicUsize = AtomicUsize::new(0);
+        let dir = std::env::temp_dir().join(format!(
+            "futures-enum-test-{}-{}",
+            std::process::id(),
+            COUNT.fetch_add(1, Ordering::Relaxed)
+        ));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn write(path: &Path, s: &str) {
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, s).unwrap();
+    }
+
+    // Returns the (name, package, version) of the dependencies in order.
+    fn deps(manifest: &Manifest) -> Vec<(&str, &str, &str)> {
+        manifest.dependencies.iter().map(|dep| (&*dep.name, &*dep.package, &*dep.version)).collect()
+    }
+
+    #[test]
+    fn dependency_tables() {
+        let dir = temp_dir();
+        let path = dir.join("Cargo.toml");
+        write(
+            &path,
+            r#"
+            [package]
+            name = "a"
+
+            [dependencies]
+            f = { package = "futures", version = "0.3.31" }
+            futures-util = "0.3"
+
+            [dev-dependencies]
+            futures-io = { version = "0.3" }
+
+            [target.'cfg(unix)'.dependencies]
+            futures-sink = "0.3"
+
+            [target.'cfg(windows)'.dev-dependencies]
+            futures-task = { path = "../futures-task" }
+        "#,
+        );
+        let manifest = Manifest::load(&path).unwrap();
+        assert_eq!(deps(&manifest), [
+            ("f", "futures", "0.3.31"),
+            ("futures_util", "futures-util", "0.3"),
+            ("futures_io", "futures-io", "0.3"),
+            ("futures_sink", "futures-sink", "0.3"),
+            ("futures_task", "futures-task", "*"),
+        ]);
+        assert_eq!(manifest.files.len(), 1);
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn workspace_dependencies() {
+        let dir = temp_dir();
+        write(
+            &dir.join("Cargo.toml"),
+            r#"
+            [workspace]
+            members = ["member"]
+
+            [workspace.dependencies]
+            f = { package = "futures", version = "0.3" }
+            futures-util = "0.3"
+        "#,
+        );
+        let path = dir.join("member/Cargo.toml");
+        write(
+            &path,
+            r#"
+            [package]
+            name = "member"
+
+            [dependencies]
+            f.workspace = true
+
+            [target.'cfg(unix)'.dependencies]
+            futures-util = { workspace = true, features = ["io"] }
+        "#,
+        );
+        let manifest = Manifest::load(&path).unwrap();
+        assert_eq!(deps(&manifest), [
+            ("f", "futures", "0.3"),
+            ("futures_util", "futures-util", "0.3"),
+        ]);
+        // The workspace root is also tracked for the cache.
+        assert_eq!(manifest.files.len(), 2);
+
+        write(
+            &path,
+            r#"
+            [package]
+            name = "member"
+
+            [dependencies]
+            futures-io.workspace = true
+        "#,
+        );
+        let e = Manifest::load(&path).err().unwrap();
+        assert!(e.contains("`futures-io` is not found in `workspace.dependencies`"), "{e}");
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn package_workspace() {
+        let dir = temp_dir();
+        // Not an ancestor of the member, so only found through `package.workspace`.
+        write(
+            &dir.join("root/Cargo.toml"),
+            r#"
+            [workspace]
+            members = ["../member"]
+
+            [workspace.dependencies]
+            futures = "0.3.5"
+        "#,
+        );
+        let path = dir.join("member/Cargo.toml");
+        write(
+            &path,
+            r#"
+            [package]
+            name = "member"
+            workspace = "../root"
+
+            [dependencies]
+            futures = { workspace = true }
+        "#,
+        );
+        let manifest = Manifest::load(&path).unwrap();
+        assert_eq!(deps(&manifest), [("futures", "futures", "0.3.5")]);
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn root_package() {
+        // A workspace root that is also the package.
+        let dir = temp_dir();
+        let path = dir.join("Cargo.toml");
+        write(
+            &path,
+            r#"
+            [package]
+            name = "a"
+
+            [workspace]
+
+            [workspace.dependencies]
+            futures = "0.3"
+
+            [dependencies]
+            futures.workspace = true
+        "#,
+        );
+        let manifest = Manifest::load(&path).unwrap();
+        assert_eq!(deps(&manifest), [("futures", "futures", "0.3")]);
+        assert_eq!(manifest.files.len(), 1);
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    // Sets the modification time of `path` to `secs` seconds after the epoch.
+    fn set_mtime(path: &Path, secs: u64) {
+        let file = fs::File::options().write(true).open(path).unwrap();
+        file.set_modified(SystemTime::UNIX_EPOCH + Duration::from_secs(secs)).unwrap();
+    }
+
+    #[test]
+    fn cache() {
+        let dir = temp_dir();
+        let root = dir.join("Cargo.toml");
+        write(
+            &root,
+            r#"
+            [workspace]
+            members = ["member"]
+
+            [workspace.dependencies]
+            futures = "0.3"
+        "#,
+        );
+        let path = dir.join("member/Cargo.toml");
+        write(
+            &path,
+            r#"
+            [package]
+            name = "member"
+
+            [dependencies]
+            futures.workspace = true
+        "#,
+        );
+        set_mtime(&root, 1);
+        set_mtime(&path, 1);
+
+        let a = Manifest::cached(path.clone()).unwrap();
+        let b = Manifest::cached(path.clone()).unwrap();
+        assert!(Arc::ptr_eq(&a, &b));
+        assert_eq!(deps(&a), [("futures", "futures", "0.3")]);
+
+        // Modifying the manifest itself invalidates the cache.
+        write(
+            &path,
+            r#"
+            [package]
+            name = "member"
+
+            [dependencies]
+            futures.workspace = true
+            futures-util = "0.3.5"
+        "#,
+        );
+        set_mtime(&path, 2);
+        let c = Manifest::cached(path.clone()).unwrap();
+        assert!(!Arc::ptr_eq(&b, &c));
+        assert_eq!(deps(&c), [
+            ("futures", "futures", "0.3"),
+            ("futures_util", "futures-util", "0.3.5"),
+        ]);
+        assert!(Arc::ptr_eq(&c, &Manifest::cached(path.clone()).unwrap()));
+
+        // So does modifying the workspace root.
+        write(
+            &root,
+            r#"
+            [workspace]
+            members = ["member"]
+
+            [workspace.dependencies]
+            futures = "0.3.31"
+        "#,
+        );
+        set_mtime(&root, 2);
+        let d = Manifest::cached(path.clone()).unwrap();
+        assert!(!Arc::ptr_eq(&c, &d));
+        assert_eq!(deps(&d), [
+            ("futures", "futures", "0.3.31"),
+            ("futures_util", "futures-util", "0.3.5"),
+        ]);
+
+        // And removing it.
+        fs::remove_file(&root).unwrap();
+        assert!(Manifest::cached(path).is_err());
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
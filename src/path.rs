@@ -0,0 +1,482 @@
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use std::env;
+
+use proc_macro2::{Span, TokenStream};
+use quote::{format_ident, quote};
+use syn::{Error, Ident, Path, Result};
+
+use crate::args::Args;
+#[cfg(feature = "renamed")]
+use crate::manifest::{Dependency, Manifest};
+
+// A module of `futures` that contains traits derived by this crate.
+#[derive(Clone, Copy)]
+pub(crate) enum Module {
+    Stream,
+    Sink,
+    Io,
+    Task,
+}
+
+impl Module {
+    fn name(self) -> &'static str {
+        match self {
+            Self::Stream => "stream",
+            Self::Sink => "sink",
+            Self::Io => "io",
+            Self::Task => "task",
+        }
+    }
+
+    // The crates that provide this module.
+    //
+    // `futures-sink` and `futures-io` come before `futures-util`, whose `sink`
+    // and `io` modules require features that may be disabled.
+    #[cfg(feature = "renamed")]
+    fn crate_names(self) -> &'static [&'static str] {
+        match self {
+            Self::Stream => {
+                &["futures", "futures-util", "futures-core", "futures-lite", "smol", "async-std"]
+            }
+            Self::Sink => &["futures-sink", "futures", "futures-util"],
+            Self::Io => {
+                &["futures-io", "futures", "futures-util", "futures-lite", "smol", "async-std"]
+            }
+            Self::Task => &["futures", "futures-util", "futures-task"],
+        }
+    }
+
+    // The environment variable that specifies the path to the sub-crate that
+    // provides this module.
+    fn env(self) -> &'static str {
+        match self {
+            Self::Stream => "FUTURES_ENUM_FUTURES_CORE",
+            Self::Sink => "FUTURES_ENUM_FUTURES_SINK",
+            Self::Io => "FUTURES_ENUM_FUTURES_IO",
+            Self::Task => "FUTURES_ENUM_FUTURES_TASK",
+        }
+    }
+
+    // The crate whose root is this module.
+    fn sub_crate(self) -> Option<&'static str> {
+        match self {
+            Self::Stream => None,
+            Self::Sink => Some("futures-sink"),
+            Self::Io => Some("futures-io"),
+            Self::Task => Some("futures-task"),
+        }
+    }
+}
+
+// The environment variable that specifies the path to `futures`.
+const FUTURES_ENV: &str = "FUTURES_ENUM_FUTURES";
+
+// Returns the path to the module that contains the traits of `module`.
+pub(crate) fn module_path(args: &Args, module: Module) -> Result<TokenStream> {
+    Ok(resolve(args, module)?.0)
+}
+
+// Returns the path to the trait `name` in `module`.
+pub(crate) fn trait_path(args: &Args, module: Module, name: &str) -> Result<TokenStream> {
+    let (path, original) = resolve(args, module)?;
+    let name = trait_name(original.as_deref(), name);
+    Ok(quote!(#path::#name))
+}
+
+// Returns the name of the trait `name` in the crate `original`.
+fn trait_name(original: Option<&str>, name: &str) -> Ident {
+    // `async-std` re-exports the IO traits of `futures-io` under different names.
+    let name = match (original, name) {
+        (Some("async-std"), "AsyncRead") => "Read",
+        (Some("async-std"), "AsyncWrite") => "Write",
+        (Some("async-std"), "AsyncSeek") => "Seek",
+        (Some("async-std"), "AsyncBufRead") => "BufRead",
+        _ => name,
+    };
+    format_ident!("{}", name)
+}
+
+// Returns the path to the module and the original name of the crate if it was
+// detected from Cargo.toml.
+//
+// Paths are looked up in the following order:
+//
+// 1. `#[futures_enum(stream = "...")]` etc.
+// 2. `#[futures_enum(crate = "...")]`
+// 3. `FUTURES_ENUM_FUTURES_CORE` environment variable etc.
+// 4. `FUTURES_ENUM_FUTURES` environment variable
+// 5. Cargo.toml (if the `renamed` feature is enabled)
+fn resolve(args: &Args, module: Module) -> Result<(TokenStream, Option<String>)> {
+    resolve_with(args, module, |var| env::var(var).ok(), crate_name)
+}
+
+// `resolve` with the environment variables read by `var` and the crate in
+// Cargo.toml found by `crate_name`.
+fn resolve_with(
+    args: &Args,
+    module: Module,
+    var: impl Fn(&str) -> Option<String>,
+    crate_name: impl FnOnce(Module) -> Result<(Ident, Option<String>)>,
+) -> Result<(TokenStream, Option<String>)> {
+    let path = match module {
+        Module::Stream => &args.stream,
+        Module::Sink => &args.sink,
+        Module::Io => &args.io,
+        Module::Task => &args.task,
+    };
+    if let Some(path) = path {
+        return Ok((quote!(#path), None));
+    }
+    let name = format_ident!("{}", module.name());
+    if let Some(crate_) = &args.crate_ {
+        return Ok((quote!(#crate_::#name), None));
+    }
+    if let Some(crate_) = env_path(module.env(), &var)? {
+        let path =
+            if module.sub_crate().is_some() { quote!(#crate_) } else { quote!(#crate_::#name) };
+        return Ok((path, None));
+    }
+    if let Some(crate_) = env_path(FUTURES_ENV, &var)? {
+        return Ok((quote!(#crate_::#name), None));
+    }
+
+    let (crate_, original) = crate_name(module)?;
+    let path = if original.is_some() && original.as_deref() == module.sub_crate() {
+        quote!(::#crate_)
+    } else {
+        quote!(::#crate_::#name)
+    };
+    Ok((path, original))
+}
+
+fn env_path(name: &str, var: impl Fn(&str) -> Option<String>) -> Result<Option<Path>> {
+    match var(name) {
+        Some(path) if !path.is_empty() => syn::parse_str(&path).map(Some).map_err(|e| {
+            Error::new(
+                Span::call_site(),
+                format!(
+                    "failed to parse the value of `{name}` environment variable as a path: {e}"
+                ),
+            )
+        }),
+        _ => Ok(None),
+    }
+}
+
+// Returns the name of the crate in the source code and its original package name.
+#[cfg(feature = "renamed")]
+fn crate_name(module: Module) -> Result<(Ident, Option<String>)> {
+    let manifest = Manifest::new().map_err(|e| not_found(module, &e))?;
+    find_crate(&manifest, module)
+}
+
+// `crate_name` with the dependencies in `manifest`.
+//
+// Crates that come first in `Module::crate_names` take precedence.
+#[cfg(feature = "renamed")]
+fn find_crate(manifest: &Manifest, module: Module) -> Result<(Ident, Option<String>)> {
+    let is_compatible = |dep: &Dependency| dep.package != "futures" || is_futures_03(&dep.version);
+    if let Some(dep) = module
+        .crate_names()
+        .iter()
+        .find_map(|&name| manifest.find(|dep| dep.package == name && is_compatible(dep)))
+    {
+        return Ok((format_ident!("{}", dep.name), Some(dep.package.clone())));
+    }
+    match manifest.find(|dep| dep.package == "futures") {
+        Some(dep) => Err(not_found(
+            module,
+            &format!(
+                "`futures` is a dependency with version requirement `{}`, which is not \
+                 compatible with 0.3",
+                dep.version
+            ),
+        )),
+        None => Err(not_found(module, "none of them is a dependency of this crate")),
+    }
+}
+
+// Returns `true` if the version requirement `req` of `futures` matches some
+// 0.3.x version, that is, the ranges of all its comparators and `>=0.3, <0.4`
+// overlap. A requirement that cannot be parsed matches nothing.
+#[cfg(feature = "renamed")]
+fn is_futures_03(req: &str) -> bool {
+    let (mut lower, mut upper) = ((0, 3, 0), (0, 4, 0));
+    for comparator in req.split(',') {
+        let Some((min, max)) = comparator_range(comparator.trim()) else { return false };
+        lower = lower.max(min);
+        if let Some(max) = max {
+            upper = upper.min(max);
+        }
+    }
+    lower < upper
+}
+
+// A version without pre-release and build metadata.
+#[cfg(feature = "renamed")]
+type Version = (u64, u64, u64);
+
+// Returns the range `min..max` of the versions that `comparator` of a Cargo
+// version requirement matches, where `max` is `None` if it is unbounded.
+#[cfg(feature = "renamed")]
+fn comparator_range(comparator: &str) -> Option<(Version, Option<Version>)> {
+    let (op, version) =
+        comparator.split_at(comparator.find(|c: char| c.is_ascii_alphanumeric() || c == '*')?);
+    // Pre-release and build metadata do not change which 0.3.x versions match.
+    let version = version.split(['-', '+']).next().unwrap();
+    let mut parts = [None; 3];
+    let mut wildcard = false;
+    for (i, part) in version.split('.').enumerate() {
+        if i == parts.len() {
+            return None;
+        }
+        if matches!(part, "*" | "x" | "X") {
+            wildcard = true;
+            break;
+        }
+        parts[i] = Some(part.parse::<u64>().ok()?);
+    }
+    let [Some(major), minor, patch] = parts else { return Some(((0, 0, 0), None)) };
+    let min = (major, minor.unwrap_or(0), patch.unwrap_or(0));
+    // The versions that `=version` matches end here.
+    let exact = match (minor, patch) {
+        (None, _) => (major + 1, 0, 0),
+        (Some(minor), None) => (major, minor + 1, 0),
+        (Some(minor), Some(patch)) => (major, minor, patch + 1),
+    };
+    Some(match op.trim() {
+        "=" => (min, Some(exact)),
+        "" if wildcard => (min, Some(exact)),
+        ">" => (exact, None),
+        ">=" => (min, None),
+        "<" => ((0, 0, 0), Some(min)),
+        "<=" => ((0, 0, 0), Some(exact)),
+        "~" => (min, Some(minor.map_or((major + 1, 0, 0), |minor| (major, minor + 1, 0)))),
+        "^" | "" => {
+            let max = match (minor, patch) {
+                _ if major > 0 => (major + 1, 0, 0),
+                (None, _) => (1, 0, 0),
+                (Some(minor), _) if minor > 0 => (0, minor + 1, 0),
+                (Some(_), None) => (0, 1, 0),
+                (Some(_), Some(patch)) => (0, 0, patch + 1),
+            };
+            (min, Some(max))
+        }
+        _ => return None,
+    })
+}
+
+#[cfg(not(feature = "renamed"))]
+#[allow(clippy::unnecessary_wraps)]
+fn crate_name(_: Module) -> Result<(Ident, Option<String>)> {
+    Ok((format_ident!("futures"), None))
+}
+
+#[cfg(feature = "renamed")]
+fn not_found(module: Module, reason: &str) -> Error {
+    let crates = module.crate_names().iter().map(|name| format!("`{name}`")).collect::<Vec<_>>();
+    Error::new(
+        Span::call_site(),
+        format!(
+            "failed to find the path to `{name}` module of `futures`: searched for {crates} in \
+             Cargo.toml, but {reason}\n\
+             help: add one of them to the dependencies, or specify the path with \
+             `#[futures_enum(crate = \"...\")]`, `#[futures_enum({name} = \"...\")]`, \
+             or `{FUTURES_ENV}` environment variable",
+            name = module.name(),
+            crates = crates.join(", "),
+        ),
+    )
+}
+
+#[cfg(all(test, feature = "renamed"))]
+mod tests {
+    use quote::{format_ident, quote};
+    use syn::{Attribute, parse_quote};
+
+    use super::{Module, find_crate, is_futures_03, resolve_with, trait_name};
+    use crate::{args::Args, manifest::Manifest};
+
+    // Returns the path to `module` with the options in `attrs` and the
+    // environment variables `vars`, where the crate detected from Cargo.toml
+    // is `manifest`.
+    fn resolve(attrs: &[Attribute], vars: &[(&str, &str)], module: Module) -> String {
+        let args = Args::new(attrs).unwrap();
+        let var = |name: &str| vars.iter().find(|(k, _)| *k == name).map(|(_, v)| (*v).to_owned());
+        let manifest = |_| Ok((format_ident!("manifest"), None));
+        resolve_with(&args, module, var, manifest).unwrap().0.to_string()
+    }
+
+    #[test]
+    fn precedence() {
+        let io = Module::Io;
+        let module: Attribute = parse_quote!(#[futures_enum(io = "attr_io", crate = "attr")]);
+        let crate_: Attribute = parse_quote!(#[futures_enum(crate = "attr")]);
+        let vars = [("FUTURES_ENUM_FUTURES_IO", "env_io"), ("FUTURES_ENUM_FUTURES", "env")];
+        assert_eq!(resolve(&[module], &vars, io), "attr_io");
+        assert_eq!(resolve(&[crate_], &vars, io), "attr :: io");
+        assert_eq!(resolve(&[], &vars, io), "env_io");
+        assert_eq!(resolve(&[], &vars[1..], io), "env :: io");
+        assert_eq!(resolve(&[], &[], io), ":: manifest :: io");
+        // `futures-core` is not the root of the `stream` module.
+        let vars = [("FUTURES_ENUM_FUTURES_CORE", "env_core"), ("FUTURES_ENUM_FUTURES", "env")];
+        assert_eq!(resolve(&[], &vars, Module::Stream), "env_core :: stream");
+        // Options that do not specify paths do not disable the variables.
+        let budget: Attribute = parse_quote!(#[futures_enum(budget = 1)]);
+        assert_eq!(resolve(&[budget], &vars, io), "env :: io");
+        // Empty variables are ignored.
+        assert_eq!(resolve(&[], &[("FUTURES_ENUM_FUTURES_IO", "")], io), ":: manifest :: io");
+    }
+
+    // Returns the path to the trait `name` that is detected from the
+    // (name, package, version) dependencies `deps`.
+    fn detect(deps: &[(&str, &str, &str)], module: Module, name: &str) -> String {
+        let manifest = Manifest::from_dependencies(deps);
+        let (path, original) = resolve_with(
+            &Args::default(),
+            module,
+            |_| None,
+            |module| find_crate(&manifest, module),
+        )
+        .unwrap();
+        let name = trait_name(original.as_deref(), name);
+        quote!(#path::#name).to_string()
+    }
+
+    #[test]
+    fn sub_crates() {
+        // The `io` and `sink` modules of `futures-util` may be disabled.
+        let deps = [
+            ("futures_util", "futures-util", "0.3"),
+            ("futures_io", "futures-io", "0.3"),
+            ("futures_sink", "futures-sink", "0.3"),
+        ];
+        assert_eq!(
+            detect(&deps, Module::Io, "AsyncRead"),
+            quote!(::futures_io::AsyncRead).to_string()
+        );
+        assert_eq!(detect(&deps, Module::Sink, "Sink"), quote!(::futures_sink::Sink).to_string());
+        assert_eq!(
+            detect(&deps, Module::Stream, "Stream"),
+            quote!(::futures_util::stream::Stream).to_string()
+        );
+    }
+
+    #[test]
+    fn runtimes() {
+        let deps = [("lite", "futures-lite", "2")];
+        assert_eq!(
+            detect(&deps, Module::Stream, "Stream"),
+            quote!(::lite::stream::Stream).to_string()
+        );
+        assert_eq!(
+            detect(&deps, Module::Io, "AsyncRead"),
+            quote!(::lite::io::AsyncRead).to_string()
+        );
+        let deps = [("smol", "smol", "2")];
+        assert_eq!(
+            detect(&deps, Module::Stream, "Stream"),
+            quote!(::smol::stream::Stream).to_string()
+        );
+        assert_eq!(
+            detect(&deps, Module::Io, "AsyncBufRead"),
+            quote!(::smol::io::AsyncBufRead).to_string()
+        );
+        // `async-std` renames the IO traits.
+        let deps = [("async_std", "async-std", "1")];
+        assert_eq!(
+            detect(&deps, Module::Stream, "Stream"),
+            quote!(::async_std::stream::Stream).to_string()
+        );
+        for (name, renamed) in [
+            ("AsyncRead", "Read"),
+            ("AsyncWrite", "Write"),
+            ("AsyncSeek", "Seek"),
+            ("AsyncBufRead", "BufRead"),
+        ] {
+            let renamed = format_ident!("{}", renamed);
+            assert_eq!(
+                detect(&deps, Module::Io, name),
+                quote!(::async_std::io::#renamed).to_string()
+            );
+        }
+        // These do not provide `Sink`.
+        let manifest = Manifest::from_dependencies(&deps);
+        assert!(find_crate(&manifest, Module::Sink).is_err());
+        // `futures` takes precedence, and its traits are not renamed.
+        let deps = [("async_std", "async-std", "1"), ("futures", "futures", "0.3")];
+        assert_eq!(
+            detect(&deps, Module::Io, "AsyncRead"),
+            quote!(::futures::io::AsyncRead).to_string()
+        );
+    }
+
+    #[test]
+    fn futures_version() {
+        for req in [
+            "*",
+            "0.3",
+            "0.3.31",
+            "^0.3",
+            "~0.3",
+            "~0.3.1",
+            "=0.3.31",
+            ">=0.3",
+            ">=0.2",
+            "<0.4",
+            "<=0.3",
+            ">=0.3, <0.4",
+            ">= 0.3, < 0.4",
+            ">0.2, <0.3.5",
+            "0.3.*",
+            "0.*",
+            "^0.3.0-alpha.1",
+        ] {
+            assert!(is_futures_03(req), "{req}");
+        }
+        for req in [
+            "0.1",
+            "^0.1.31",
+            "<0.3",
+            ">0.3",
+            ">=0.4",
+            "0.2",
+            "0.2.*",
+            "1",
+            "0.30",
+            ">=0.3.1, <0.3.1",
+            "0.3.1.1",
+            "foo",
+        ] {
+            assert!(!is_futures_03(req), "{req}");
+        }
+    }
+
+    #[test]
+    fn not_found() {
+        let error = |deps: &[(&str, &str, &str)]| {
+            find_crate(&Manifest::from_dependencies(deps), Module::Sink).unwrap_err().to_string()
+        };
+        let help = "help: add one of them to the dependencies, or specify the path with \
+                    `#[futures_enum(crate = \"...\")]`, `#[futures_enum(sink = \"...\")]`, or \
+                    `FUTURES_ENUM_FUTURES` environment variable";
+        assert_eq!(
+            error(&[("futures_core", "futures-core", "0.3")]),
+            format!(
+                "failed to find the path to `sink` module of `futures`: searched for \
+                 `futures-sink`, `futures`, `futures-util` in Cargo.toml, but none of them is a \
+                 dependency of this crate\n{help}"
+            )
+        );
+        assert_eq!(
+            error(&[("futures", "futures", "0.1")]),
+            format!(
+                "failed to find the path to `sink` module of `futures`: searched for \
+                 `futures-sink`, `futures`, `futures-util` in Cargo.toml, but `futures` is a \
+                 dependency with version requirement `0.1`, which is not compatible with 0.3\n\
+                 {help}"
+            )
+        );
+    }
+}
@@ -0,0 +1,87 @@
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+// `#[futures_enum(then = "path", output = "Type")]`: implements `Future` for an enum whose
+// variants are the states of a state machine.
+
+use derive_utils::EnumData;
+use proc_macro2::{Span, TokenStream};
+use quote::{format_ident, quote, quote_spanned};
+use syn::{
+    Fields, Ident, ItemEnum, ItemImpl, Path, Type, WherePredicate, parse_quote,
+    spanned::Spanned as _,
+};
+
+use crate::args::Args;
+
+// Generates `<Enum>Output`, which holds the output of each variant, and a
+// `Future` impl that, when the active variant completes, passes its output to
+// `then`, and either returns the output of `ControlFlow::Break` or replaces
+// itself with the variant in `ControlFlow::Continue` and polls it.
+//
+// The output of the future is `output_ty`, which the type of `ControlFlow::Break`
+// must match.
+pub(crate) fn derive(data: &EnumData, args: &Args, then: &Path, output_ty: &Type) -> TokenStream {
+    let ident = &data.ident;
+    let output_ident = format_ident!("{}Output", ident);
+
+    let mut generics = data.generics.clone();
+    generics.make_where_clause().predicates.extend(
+        data.field_types()
+            .map(|ty| -> WherePredicate { parse_quote!(#ty: ::core::future::Future) }),
+    );
+    let mut item = ItemEnum::clone(data);
+    let doc = format!(" The outputs of the variants of [`{ident}`].");
+    item.attrs = vec![parse_quote!(#[doc = #doc])];
+    item.ident = output_ident.clone();
+    item.generics = generics.clone();
+    for (v, ty) in item.variants.iter_mut().zip(data.field_types()) {
+        v.fields = Fields::Unnamed(parse_quote!((<#ty as ::core::future::Future>::Output)));
+    }
+
+    let x = Ident::new("x", Span::mixed_site());
+    let output = Ident::new("output", Span::mixed_site());
+    let next = Ident::new("next", Span::mixed_site());
+    let arms = data.variant_idents().zip(data.field_types()).map(|(v, ty)| {
+        quote! {
+            #ident::#v(#x) => match <#ty as ::core::future::Future>::poll(
+                ::core::pin::Pin::new_unchecked(#x),
+                cx,
+            ) {
+                ::core::task::Poll::Ready(#x) => #output_ident::#v(#x),
+                ::core::task::Poll::Pending => break ::core::task::Poll::Pending,
+            },
+        }
+    });
+    let call = quote_spanned!(then.span()=> #then(#output));
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    let impl_: ItemImpl = parse_quote! {
+        #[allow(unsafe_code)]
+        impl #impl_generics ::core::future::Future for #ident #ty_generics #where_clause {
+            type Output = #output_ty;
+            #[inline]
+            fn poll(
+                mut self: ::core::pin::Pin<&mut Self>,
+                cx: &mut ::core::task::Context<'_>,
+            ) -> ::core::task::Poll<Self::Output> {
+                loop {
+                    let #output = unsafe {
+                        match self.as_mut().get_unchecked_mut() {
+                            #(#arms)*
+                        }
+                    };
+                    match #call {
+                        ::core::ops::ControlFlow::Break(#output) => {
+                            break ::core::task::Poll::Ready(#output);
+                        }
+                        ::core::ops::ControlFlow::Continue(#next) => self.set(#next),
+                    }
+                }
+            }
+        }
+    };
+    let impl_ = crate::finish(impl_, data, args);
+    quote! {
+        #item
+        #impl_
+    }
+}
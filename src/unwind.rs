@@ -0,0 +1,129 @@
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+// Converts panics in the variants of `Future` and `Stream` into values.
+
+use derive_utils::EnumData;
+use proc_macro2::{Span, TokenStream};
+use quote::{format_ident, quote, quote_spanned};
+use syn::{Ident, ImplItem, ItemImpl, ItemTrait, Path, parse_quote, spanned::Spanned as _};
+
+use crate::{instrument::variant_name, wrapper};
+
+// Wraps `poll` of `item` in `catch_unwind`, and changes `Output` to
+// `Result<_, #panic>`, where a panic is converted by
+// `#panic::new(variant, payload)`.
+//
+// A future is not polled after it completes, so it needs no state for the
+// panic, unlike a stream (see `stream`).
+pub(crate) fn catch_unwind(item: &mut ItemImpl, data: &EnumData, panic: &Path) {
+    let variant = Ident::new("variant", Span::mixed_site());
+    let payload = Ident::new("payload", Span::mixed_site());
+    let x = Ident::new("x", Span::mixed_site());
+    let variant_name = variant_name(data, &quote!(*self));
+    for item in &mut item.items {
+        match item {
+            ImplItem::Type(ty) if ty.ident == "Output" => {
+                let inner = &ty.ty;
+                ty.ty = parse_quote!(::core::result::Result<#inner, #panic>);
+            }
+            ImplItem::Method(method) if method.sig.ident == "poll" => {
+                let err = quote_spanned! {panic.span()=>
+                    ::core::result::Result::Err(#panic::new(#variant, #payload))
+                };
+                let block = &method.block;
+                let f = quote!(::std::panic::AssertUnwindSafe(move || #block));
+                method.block = parse_quote!({
+                    let #variant = #variant_name;
+                    match ::std::panic::catch_unwind(#f) {
+                        ::core::result::Result::Ok(::core::task::Poll::Ready(#x)) => {
+                            ::core::task::Poll::Ready(::core::result::Result::Ok(#x))
+                        }
+                        ::core::result::Result::Err(#payload) => ::core::task::Poll::Ready(#err),
+                        ::core::result::Result::Ok(::core::task::Poll::Pending) => {
+                            ::core::task::Poll::Pending
+                        }
+                    }
+                });
+            }
+            _ => {}
+        }
+    }
+}
+
+// Generates `<Enum>CatchUnwind`, a stream that wraps the enum, polls it in
+// `catch_unwind`, and yields `Result<Item, #panic>`. Like
+// `futures::stream::CatchUnwind`, it ends after the item for a panic, because
+// the variant that panicked cannot be polled again.
+pub(crate) fn stream(
+    data: &EnumData,
+    path: &Path,
+    trait_def: &ItemTrait,
+    panic: &Path,
+) -> TokenStream {
+    let ident = format_ident!("{}CatchUnwind", data.ident);
+    let panicked = Ident::new("panicked", Span::call_site());
+    let doc = format!(
+        " A stream that wraps [`{}`] and returns panics in its variants as errors, created by \
+         `{ident}::new`.",
+        data.ident
+    );
+    let def = wrapper::define(data, &ident, &doc, (&panicked, parse_quote!(bool), quote!(false)));
+
+    let this = wrapper::this();
+    let variant = Ident::new("variant", Span::mixed_site());
+    let payload = Ident::new("payload", Span::mixed_site());
+    let x = Ident::new("x", Span::mixed_site());
+    let f = Ident::new("f", Span::mixed_site());
+    let variant_name = variant_name(data, &quote!(#this.inner));
+    let mut item = wrapper::delegate(data, &ident, path, trait_def);
+    for item in &mut item.items {
+        match item {
+            ImplItem::Type(ty) if ty.ident == "Item" => {
+                let inner = &ty.ty;
+                ty.ty = parse_quote!(::core::result::Result<#inner, #panic>);
+            }
+            ImplItem::Method(method) if method.sig.ident == "poll_next" => {
+                let call = wrapper::call(data, path, &method.sig);
+                let err = quote_spanned! {panic.span()=>
+                    ::core::result::Result::Err(#panic::new(#variant, #payload))
+                };
+                method.block = parse_quote!({
+                    let #this = unsafe { self.get_unchecked_mut() };
+                    if #this.#panicked {
+                        return ::core::task::Poll::Ready(::core::option::Option::None);
+                    }
+                    let #variant = #variant_name;
+                    let #f = ::std::panic::AssertUnwindSafe(|| unsafe { #call });
+                    match ::std::panic::catch_unwind(#f) {
+                        ::core::result::Result::Ok(::core::task::Poll::Ready(
+                            ::core::option::Option::Some(#x),
+                        )) => ::core::task::Poll::Ready(::core::option::Option::Some(
+                            ::core::result::Result::Ok(#x),
+                        )),
+                        ::core::result::Result::Ok(::core::task::Poll::Ready(
+                            ::core::option::Option::None,
+                        )) => ::core::task::Poll::Ready(::core::option::Option::None),
+                        ::core::result::Result::Ok(::core::task::Poll::Pending) => {
+                            ::core::task::Poll::Pending
+                        }
+                        ::core::result::Result::Err(#payload) => {
+                            #this.#panicked = true;
+                            ::core::task::Poll::Ready(::core::option::Option::Some(#err))
+                        }
+                    }
+                });
+            }
+            ImplItem::Method(method) if method.sig.ident == "size_hint" => {
+                let block = &method.block;
+                method.block = parse_quote!({
+                    if self.#panicked { (0, ::core::option::Option::Some(0)) } else #block
+                });
+            }
+            _ => {}
+        }
+    }
+    quote! {
+        #def
+        #item
+    }
+}
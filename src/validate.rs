@@ -0,0 +1,111 @@
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use quote::ToTokens;
+use syn::{Data, DeriveInput, Error, Fields, Ident, Result};
+
+// Checks that `input` is an enum that `#[derive(#derive)]` supports, that is,
+// an enum with at least one variant, where each variant has exactly one
+// unnamed field and no discriminant.
+//
+// This reports all offending variants at once, on the spans of the variants.
+pub(crate) fn validate(input: &DeriveInput, derive: &str) -> Result<()> {
+    let data = match &input.data {
+        Data::Enum(data) => data,
+        Data::Struct(data) => {
+            let join = match derive {
+                "Future" => {
+                    ", or `#[derive(IntoFuture)]` with `#[futures_enum(join)]` to join the \
+                     futures in the fields"
+                }
+                "IntoFuture" => ", or `#[futures_enum(join)]` to join the futures in the fields",
+                _ => "",
+            };
+            let help = if uses_fields(derive) {
+                format!("use an enum with a variant for each type that implements `{derive}`")
+            } else {
+                "use an enum with a variant for each type".to_owned()
+            };
+            return Err(Error::new(
+                data.struct_token.span,
+                format!("`#[derive({derive})]` may only be used on enums; {help}{join}"),
+            ));
+        }
+        Data::Union(data) => {
+            return Err(Error::new(
+                data.union_token.span,
+                format!("`#[derive({derive})]` may only be used on enums"),
+            ));
+        }
+    };
+    if data.variants.is_empty() {
+        let help = if uses_fields(derive) {
+            format!("add a variant for each type that implements `{derive}`, such as `A(A)`")
+        } else {
+            "add a variant with one field, such as `A(A)`".to_owned()
+        };
+        return Err(Error::new_spanned(
+            &input.ident,
+            format!("`#[derive({derive})]` may not be used on enums without variants; {help}"),
+        ));
+    }
+
+    let mut errors: Option<Error> = None;
+    let mut push = |e: Error| match &mut errors {
+        Some(errors) => errors.combine(e),
+        None => errors = Some(e),
+    };
+    for v in &data.variants {
+        let ident = &v.ident;
+        if let Some((_, discriminant)) = &v.discriminant {
+            push(Error::new_spanned(
+                discriminant,
+                format!(
+                    "`#[derive({derive})]` may not be used on enums with discriminants; \
+                     remove the discriminant of variant `{ident}`"
+                ),
+            ));
+        }
+        let (tokens, msg): (&dyn ToTokens, _) = match &v.fields {
+            Fields::Unnamed(f) if f.unnamed.len() == 1 => continue,
+            Fields::Unit => (ident, unit_variant(ident, derive)),
+            Fields::Named(f) if f.named.len() == 1 => (
+                f,
+                format!("variant `{ident}` has a named field; use a tuple variant `{ident}(T)`"),
+            ),
+            Fields::Named(f) => (f, many_fields(ident, f.named.len(), derive)),
+            Fields::Unnamed(f) => (f, many_fields(ident, f.unnamed.len(), derive)),
+        };
+        push(Error::new_spanned(
+            tokens,
+            format!("`#[derive({derive})]` requires each variant to have exactly one field: {msg}"),
+        ));
+    }
+    errors.map_or(Ok(()), Err)
+}
+
+fn unit_variant(ident: &Ident, derive: &str) -> String {
+    if uses_fields(derive) {
+        format!(
+            "variant `{ident}` has no fields; use `{ident}(T)`, where `T` implements `{derive}`"
+        )
+    } else {
+        format!("variant `{ident}` has no fields; use `{ident}(T)` with a field of any type")
+    }
+}
+
+fn many_fields(ident: &Ident, len: usize, derive: &str) -> String {
+    if uses_fields(derive) {
+        format!(
+            "variant `{ident}` has {len} fields; use a single field of a type that implements \
+             `{derive}`"
+        )
+    } else {
+        format!("variant `{ident}` has {len} fields; use a single field, such as a tuple")
+    }
+}
+
+// Returns `false` for the derives whose impls do not use the fields, so the
+// fields do not need to implement `derive`.
+fn uses_fields(derive: &str) -> bool {
+    !matches!(derive, "VariantName" | "OpaqueDebug")
+}
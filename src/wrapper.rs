@@ -0,0 +1,144 @@
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+// Structs generated next to the enum that hold the state that the options need
+// and the enum has no room for.
+
+use derive_utils::EnumData;
+use proc_macro2::{Span, TokenStream};
+use quote::{ToTokens as _, quote};
+use syn::{
+    FnArg, Ident, ImplItem, ItemImpl, ItemTrait, Pat, Path, Signature, TraitItem, Type, parse_quote,
+};
+
+use crate::hygiene;
+
+// Defines the struct `ident`, which holds the enum in `inner` and the state in
+// `field`, and its constructor and accessors.
+pub(crate) fn define(
+    data: &EnumData,
+    ident: &Ident,
+    doc: &str,
+    (field, ty, init): (&Ident, Type, TokenStream),
+) -> TokenStream {
+    let vis = &data.vis;
+    let enum_ident = &data.ident;
+    let generics = &data.generics;
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    let new_doc = format!(" Creates a new `{ident}` that wraps `inner`.");
+    quote! {
+        #[doc = #doc]
+        #[allow(dead_code)]
+        #vis struct #ident #generics #where_clause {
+            inner: #enum_ident #ty_generics,
+            #field: #ty,
+        }
+
+        #[allow(dead_code)]
+        impl #impl_generics #ident #ty_generics #where_clause {
+            #[doc = #new_doc]
+            #vis fn new(inner: #enum_ident #ty_generics) -> Self {
+                Self { inner, #field: #init }
+            }
+            /// Returns a reference to the wrapped enum.
+            #vis fn get_ref(&self) -> &#enum_ident #ty_generics {
+                &self.inner
+            }
+            /// Returns a mutable reference to the wrapped enum.
+            #vis fn get_mut(&mut self) -> &mut #enum_ident #ty_generics {
+                &mut self.inner
+            }
+            /// Returns the wrapped enum.
+            #vis fn into_inner(self) -> #enum_ident #ty_generics {
+                self.inner
+            }
+        }
+    }
+}
+
+// Implements `trait_def` for the struct `ident` by delegating each method to
+// the impl of the enum. The generics of `trait_def` are renamed as in
+// `derive`. In methods that take `self: Pin<&mut Self>`, the body binds
+// `this()` to `&mut Self`, and the call to the enum is made on `inner` pinned
+// in place, as `inner` is never moved out of a pinned struct.
+pub(crate) fn delegate(
+    data: &EnumData,
+    ident: &Ident,
+    path: &Path,
+    trait_def: &ItemTrait,
+) -> ItemImpl {
+    let enum_ident = &data.ident;
+    let (impl_generics, ty_generics, where_clause) = data.generics.split_for_impl();
+    let enum_ty = quote!(#enum_ident #ty_generics);
+    let mut where_clause = where_clause.cloned().unwrap_or_else(|| parse_quote!(where));
+    where_clause.predicates.push(parse_quote!(#enum_ty: #path));
+
+    let mut trait_def = trait_def.clone();
+    hygiene::rename_generics(&mut trait_def, &data.generics);
+    let this = this();
+    let items = trait_def.items.iter().map(|item| -> ImplItem {
+        match item {
+            TraitItem::Type(ty) => {
+                let assoc = &ty.ident;
+                parse_quote!(type #assoc = <#enum_ty as #path>::#assoc;)
+            }
+            TraitItem::Method(method) => {
+                let attrs = &method.attrs;
+                let sig = &method.sig;
+                let call = call(data, path, sig);
+                if is_pinned(sig) {
+                    parse_quote! {
+                        #(#attrs)*
+                        #sig {
+                            let #this = unsafe { self.get_unchecked_mut() };
+                            unsafe { #call }
+                        }
+                    }
+                } else {
+                    parse_quote!(#(#attrs)* #sig { #call })
+                }
+            }
+            _ => unreachable!(),
+        }
+    });
+    parse_quote! {
+        #[allow(unsafe_code)]
+        impl #impl_generics #path for #ident #ty_generics #where_clause {
+            #(#items)*
+        }
+    }
+}
+
+// Returns the call of the method with the signature `sig` on the enum in
+// `inner`, which must be in an unsafe block if the method takes
+// `self: Pin<&mut Self>`.
+pub(crate) fn call(data: &EnumData, path: &Path, sig: &Signature) -> TokenStream {
+    let enum_ident = &data.ident;
+    let ty_generics = data.generics.split_for_impl().1;
+    let method = &sig.ident;
+    let this = this();
+    let args = sig.inputs.iter().map(|arg| match arg {
+        FnArg::Receiver(_) => quote!(&self.inner),
+        FnArg::Typed(arg) => match &*arg.pat {
+            Pat::Ident(pat) if pat.ident == "self" => {
+                quote!(::core::pin::Pin::new_unchecked(&mut #this.inner))
+            }
+            Pat::Ident(pat) => pat.ident.to_token_stream(),
+            _ => unreachable!(),
+        },
+    });
+    quote!(<#enum_ident #ty_generics as #path>::#method(#(#args),*))
+}
+
+// The `&mut Self` of the struct in methods that take `self: Pin<&mut Self>`.
+pub(crate) fn this() -> Ident {
+    Ident::new("this", Span::mixed_site())
+}
+
+// Returns `true` if the method takes `self: Pin<&mut Self>`, the only typed
+// receiver in the traits.
+pub(crate) fn is_pinned(sig: &Signature) -> bool {
+    match sig.inputs.first() {
+        Some(FnArg::Typed(arg)) => matches!(&*arg.pat, Pat::Ident(pat) if pat.ident == "self"),
+        _ => false,
+    }
+}
@@ -0,0 +1,10 @@
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+#![cfg(not(miri))]
+
+#[rustversion::attr(not(nightly), ignore = "the output of rustc differs between versions")]
+#[test]
+fn ui() {
+    let t = trybuild::TestCases::new();
+    t.compile_fail("tests/ui/**/*.rs");
+}
@@ -0,0 +1,119 @@
+use std::{future::Ready, pin::Pin};
+use futures_enum::*;
+#[futures_enum(assert(Send, Unpin))]
+enum Enum<'a, A> {
+    A(Ready<()>),
+    B(Pin<Box<dyn Future<Output = ()> + Send + 'a>>),
+    C(A),
+}
+const _: () = {
+    #[diagnostic::on_unimplemented(
+        message = "variant `B` has `Output = {U}`, but variant `A` has `Output = {T}`",
+        label = "expected `Output = {T}`",
+        note = "all variants must have the same `Output`"
+    )]
+    trait __AssertOutput1<
+        T: ?::core::marker::Sized,
+        U: ?::core::marker::Sized,
+    >: ::core::future::Future<Output = T> {}
+    #[diagnostic::do_not_recommend]
+    impl<F, T: ?::core::marker::Sized> __AssertOutput1<T, T> for F
+    where
+        F: ?::core::marker::Sized + ::core::future::Future<Output = T>,
+    {}
+    #[diagnostic::on_unimplemented(
+        message = "variant `C` has `Output = {U}`, but variant `A` has `Output = {T}`",
+        label = "expected `Output = {T}`",
+        note = "all variants must have the same `Output`"
+    )]
+    trait __AssertOutput2<
+        T: ?::core::marker::Sized,
+        U: ?::core::marker::Sized,
+    >: ::core::future::Future<Output = T> {}
+    #[diagnostic::do_not_recommend]
+    impl<F, T: ?::core::marker::Sized> __AssertOutput2<T, T> for F
+    where
+        F: ?::core::marker::Sized + ::core::future::Future<Output = T>,
+    {}
+    const _: () = {
+        #[diagnostic::on_unimplemented(
+            message = "variant `C` does not implement `Send`",
+            label = "`{Self}` does not implement `Send`",
+            note = "required by `#[futures_enum(assert(Send))]`"
+        )]
+        trait __AssertSend2 {}
+        #[diagnostic::do_not_recommend]
+        impl<T: ?::core::marker::Sized + ::core::marker::Send> __AssertSend2 for T {}
+        #[diagnostic::on_unimplemented(
+            message = "variant `C` does not implement `Unpin`",
+            label = "`{Self}` does not implement `Unpin`",
+            note = "required by `#[futures_enum(assert(Unpin))]`"
+        )]
+        trait __AssertUnpin2 {}
+        #[diagnostic::do_not_recommend]
+        impl<T: ?::core::marker::Sized + ::core::marker::Unpin> __AssertUnpin2 for T {}
+        #[allow(unsafe_code)]
+        impl<'a, A> ::core::future::Future for Enum<'a, A>
+        where
+            Ready<()>: ::core::future::Future,
+            Pin<Box<dyn Future<Output = ()> + Send + 'a>>: ::core::future::Future,
+            A: ::core::future::Future,
+            Pin<
+                Box<dyn Future<Output = ()> + Send + 'a>,
+            >: __AssertOutput1<
+                <Ready<()> as ::core::future::Future>::Output,
+                <Pin<
+                    Box<dyn Future<Output = ()> + Send + 'a>,
+                > as ::core::future::Future>::Output,
+            >,
+            A: __AssertOutput2<
+                <Ready<()> as ::core::future::Future>::Output,
+                <A as ::core::future::Future>::Output,
+            >,
+            A: __AssertSend2,
+            A: __AssertUnpin2,
+        {
+            type Output = <Ready<()> as ::core::future::Future>::Output;
+            #[inline]
+            fn poll(
+                self: ::core::pin::Pin<&mut Self>,
+                cx: &mut ::core::task::Context<'_>,
+            ) -> ::core::task::Poll<Self::Output> {
+                unsafe {
+                    match self.get_unchecked_mut() {
+                        Enum::A(x) => {
+                            ::core::future::Future::poll(
+                                ::core::pin::Pin::new_unchecked(x),
+                                cx,
+                            )
+                        }
+                        Enum::B(x) => {
+                            ::core::future::Future::poll(
+                                ::core::pin::Pin::new_unchecked(x),
+                                cx,
+                            )
+                        }
+                        Enum::C(x) => {
+                            ::core::future::Future::poll(
+                                ::core::pin::Pin::new_unchecked(x),
+                                cx,
+                            )
+                        }
+                    }
+                }
+            }
+        }
+    };
+};
+const _: () = {
+    #[allow(dead_code)]
+    fn __assert<'a, A>() {
+        fn assert_send<T: ?::core::marker::Sized + ::core::marker::Send>() {}
+        fn assert_unpin<T: ?::core::marker::Sized + ::core::marker::Unpin>() {}
+        assert_send::<Ready<()>>();
+        assert_send::<Pin<Box<dyn Future<Output = ()> + Send + 'a>>>();
+        assert_unpin::<Ready<()>>();
+        assert_unpin::<Pin<Box<dyn Future<Output = ()> + Send + 'a>>>();
+    }
+};
+fn main() {}
@@ -0,0 +1,15 @@
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use std::{future::Ready, pin::Pin};
+
+use futures_enum::*;
+
+#[derive(Future)]
+#[futures_enum(assert(Send, Unpin))]
+enum Enum<'a, A> {
+    A(Ready<()>),
+    B(Pin<Box<dyn Future<Output = ()> + Send + 'a>>),
+    C(A),
+}
+
+fn main() {}
@@ -0,0 +1,96 @@
+use std::future::Ready;
+use futures_enum::*;
+enum Enum<A> {
+    A(Ready<u8>),
+    B(A),
+    C(Ready<u8>),
+}
+const _: () = {
+    #[diagnostic::on_unimplemented(
+        message = "variant `B` has `Output = {U}`, but variant `A` has `Output = {T}`",
+        label = "expected `Output = {T}`",
+        note = "all variants must have the same `Output`"
+    )]
+    trait __AssertOutput1<
+        T: ?::core::marker::Sized,
+        U: ?::core::marker::Sized,
+    >: ::core::future::Future<Output = T> {}
+    #[diagnostic::do_not_recommend]
+    impl<F, T: ?::core::marker::Sized> __AssertOutput1<T, T> for F
+    where
+        F: ?::core::marker::Sized + ::core::future::Future<Output = T>,
+    {}
+    #[diagnostic::on_unimplemented(
+        message = "variant `C` has `Output = {U}`, but variant `A` has `Output = {T}`",
+        label = "expected `Output = {T}`",
+        note = "all variants must have the same `Output`"
+    )]
+    trait __AssertOutput2<
+        T: ?::core::marker::Sized,
+        U: ?::core::marker::Sized,
+    >: ::core::future::Future<Output = T> {}
+    #[diagnostic::do_not_recommend]
+    impl<F, T: ?::core::marker::Sized> __AssertOutput2<T, T> for F
+    where
+        F: ?::core::marker::Sized + ::core::future::Future<Output = T>,
+    {}
+    let _ = || {
+        fn assert<T>()
+        where
+            T: ?::core::marker::Sized + ::core::future::Future,
+            T: __AssertOutput2<
+                <Ready<u8> as ::core::future::Future>::Output,
+                <T as ::core::future::Future>::Output,
+            >,
+        {}
+        assert::<Ready<u8>>();
+    };
+    #[allow(unsafe_code)]
+    impl<A> ::core::future::Future for Enum<A>
+    where
+        Ready<u8>: ::core::future::Future,
+        A: ::core::future::Future,
+        Ready<u8>: ::core::future::Future,
+        A: __AssertOutput1<
+            <Ready<u8> as ::core::future::Future>::Output,
+            <A as ::core::future::Future>::Output,
+        >,
+        for<'__a> Ready<
+            u8,
+        >: __AssertOutput2<
+            <Ready<u8> as ::core::future::Future>::Output,
+            <Ready<u8> as ::core::future::Future>::Output,
+        >,
+    {
+        type Output = <Ready<u8> as ::core::future::Future>::Output;
+        #[inline]
+        fn poll(
+            self: ::core::pin::Pin<&mut Self>,
+            cx: &mut ::core::task::Context<'_>,
+        ) -> ::core::task::Poll<Self::Output> {
+            unsafe {
+                match self.get_unchecked_mut() {
+                    Enum::A(x) => {
+                        ::core::future::Future::poll(
+                            ::core::pin::Pin::new_unchecked(x),
+                            cx,
+                        )
+                    }
+                    Enum::B(x) => {
+                        ::core::future::Future::poll(
+                            ::core::pin::Pin::new_unchecked(x),
+                            cx,
+                        )
+                    }
+                    Enum::C(x) => {
+                        ::core::future::Future::poll(
+                            ::core::pin::Pin::new_unchecked(x),
+                            cx,
+                        )
+                    }
+                }
+            }
+        }
+    }
+};
+fn main() {}
@@ -0,0 +1,14 @@
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use std::future::Ready;
+
+use futures_enum::*;
+
+#[derive(Future)]
+enum Enum<A> {
+    A(Ready<u8>),
+    B(A),
+    C(Ready<u8>),
+}
+
+fn main() {}
@@ -0,0 +1,64 @@
+#![feature(async_iterator)]
+use futures_enum::*;
+enum Enum<A, B> {
+    A(A),
+    B(B),
+}
+const _: () = {
+    #[diagnostic::on_unimplemented(
+        message = "variant `B` has `Item = {U}`, but variant `A` has `Item = {T}`",
+        label = "expected `Item = {T}`",
+        note = "all variants must have the same `Item`"
+    )]
+    trait __AssertItem1<
+        T: ?::core::marker::Sized,
+        U: ?::core::marker::Sized,
+    >: ::core::async_iter::AsyncIterator<Item = T> {}
+    #[diagnostic::do_not_recommend]
+    impl<F, T: ?::core::marker::Sized> __AssertItem1<T, T> for F
+    where
+        F: ?::core::marker::Sized + ::core::async_iter::AsyncIterator<Item = T>,
+    {}
+    #[allow(unsafe_code)]
+    impl<A, B> ::core::async_iter::AsyncIterator for Enum<A, B>
+    where
+        A: ::core::async_iter::AsyncIterator,
+        B: ::core::async_iter::AsyncIterator,
+        B: __AssertItem1<
+            <A as ::core::async_iter::AsyncIterator>::Item,
+            <B as ::core::async_iter::AsyncIterator>::Item,
+        >,
+    {
+        type Item = <A as ::core::async_iter::AsyncIterator>::Item;
+        #[inline]
+        fn poll_next(
+            self: ::core::pin::Pin<&mut Self>,
+            cx: &mut ::core::task::Context<'_>,
+        ) -> ::core::task::Poll<::core::option::Option<Self::Item>> {
+            unsafe {
+                match self.get_unchecked_mut() {
+                    Enum::A(x) => {
+                        ::core::async_iter::AsyncIterator::poll_next(
+                            ::core::pin::Pin::new_unchecked(x),
+                            cx,
+                        )
+                    }
+                    Enum::B(x) => {
+                        ::core::async_iter::AsyncIterator::poll_next(
+                            ::core::pin::Pin::new_unchecked(x),
+                            cx,
+                        )
+                    }
+                }
+            }
+        }
+        #[inline]
+        fn size_hint(&self) -> (usize, ::core::option::Option<usize>) {
+            match self {
+                Enum::A(x) => ::core::async_iter::AsyncIterator::size_hint(x),
+                Enum::B(x) => ::core::async_iter::AsyncIterator::size_hint(x),
+            }
+        }
+    }
+};
+fn main() {}
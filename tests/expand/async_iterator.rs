@@ -0,0 +1,13 @@
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+#![feature(async_iterator)]
+
+use futures_enum::*;
+
+#[derive(AsyncIterator)]
+enum Enum<A, B> {
+    A(A),
+    B(B),
+}
+
+fn main() {}
@@ -0,0 +1,584 @@
+use futures_enum::*;
+#[futures_enum(budget = 128)]
+enum Enum<A, B> {
+    A(A),
+    B(B),
+}
+const _: () = {
+    #[diagnostic::on_unimplemented(
+        message = "variant `B` has `Item = {U}`, but variant `A` has `Item = {T}`",
+        label = "expected `Item = {T}`",
+        note = "all variants must have the same `Item`"
+    )]
+    trait __AssertItem1<
+        T: ?::core::marker::Sized,
+        U: ?::core::marker::Sized,
+    >: ::futures::stream::Stream<Item = T> {}
+    #[diagnostic::do_not_recommend]
+    impl<F, T: ?::core::marker::Sized> __AssertItem1<T, T> for F
+    where
+        F: ?::core::marker::Sized + ::futures::stream::Stream<Item = T>,
+    {}
+    #[allow(unsafe_code)]
+    impl<A, B> ::futures::stream::Stream for Enum<A, B>
+    where
+        A: ::futures::stream::Stream,
+        B: ::futures::stream::Stream,
+        B: __AssertItem1<
+            <A as ::futures::stream::Stream>::Item,
+            <B as ::futures::stream::Stream>::Item,
+        >,
+    {
+        type Item = <A as ::futures::stream::Stream>::Item;
+        #[inline]
+        fn poll_next(
+            self: ::core::pin::Pin<&mut Self>,
+            cx: &mut ::core::task::Context<'_>,
+        ) -> ::core::task::Poll<::core::option::Option<Self::Item>> {
+            unsafe {
+                match self.get_unchecked_mut() {
+                    Enum::A(x) => {
+                        ::futures::stream::Stream::poll_next(
+                            ::core::pin::Pin::new_unchecked(x),
+                            cx,
+                        )
+                    }
+                    Enum::B(x) => {
+                        ::futures::stream::Stream::poll_next(
+                            ::core::pin::Pin::new_unchecked(x),
+                            cx,
+                        )
+                    }
+                }
+            }
+        }
+        #[inline]
+        fn size_hint(&self) -> (usize, ::core::option::Option<usize>) {
+            match self {
+                Enum::A(x) => ::futures::stream::Stream::size_hint(x),
+                Enum::B(x) => ::futures::stream::Stream::size_hint(x),
+            }
+        }
+    }
+};
+/// A stream that wraps [`Enum`] and yields to the executor after a number of consecutive `Ready` results, created by `EnumBudgetedStream::new`.
+#[allow(dead_code)]
+struct EnumBudgetedStream<A, B> {
+    inner: Enum<A, B>,
+    ready: usize,
+}
+#[allow(dead_code)]
+impl<A, B> EnumBudgetedStream<A, B> {
+    /// Creates a new `EnumBudgetedStream` that wraps `inner`.
+    fn new(inner: Enum<A, B>) -> Self {
+        Self { inner, ready: 0 }
+    }
+    /// Returns a reference to the wrapped enum.
+    fn get_ref(&self) -> &Enum<A, B> {
+        &self.inner
+    }
+    /// Returns a mutable reference to the wrapped enum.
+    fn get_mut(&mut self) -> &mut Enum<A, B> {
+        &mut self.inner
+    }
+    /// Returns the wrapped enum.
+    fn into_inner(self) -> Enum<A, B> {
+        self.inner
+    }
+}
+#[allow(unsafe_code)]
+impl<A, B> ::futures::stream::Stream for EnumBudgetedStream<A, B>
+where
+    Enum<A, B>: ::futures::stream::Stream,
+{
+    type Item = <Enum<A, B> as ::futures::stream::Stream>::Item;
+    #[inline]
+    fn poll_next(
+        self: ::core::pin::Pin<&mut Self>,
+        cx: &mut ::core::task::Context<'_>,
+    ) -> ::core::task::Poll<::core::option::Option<Self::Item>> {
+        let this = unsafe { self.get_unchecked_mut() };
+        if this.ready >= 128usize {
+            this.ready = 0;
+            cx.waker().wake_by_ref();
+            return ::core::task::Poll::Pending;
+        }
+        let poll = unsafe {
+            <Enum<
+                A,
+                B,
+            > as ::futures::stream::Stream>::poll_next(
+                ::core::pin::Pin::new_unchecked(&mut this.inner),
+                cx,
+            )
+        };
+        this.ready = if poll.is_ready() { this.ready + 1 } else { 0 };
+        poll
+    }
+    #[inline]
+    fn size_hint(&self) -> (usize, ::core::option::Option<usize>) {
+        <Enum<A, B> as ::futures::stream::Stream>::size_hint(&self.inner)
+    }
+}
+#[allow(unsafe_code)]
+impl<A, B> ::futures::io::AsyncRead for Enum<A, B>
+where
+    A: ::futures::io::AsyncRead,
+    B: ::futures::io::AsyncRead,
+{
+    #[inline]
+    fn poll_read(
+        self: ::core::pin::Pin<&mut Self>,
+        cx: &mut ::core::task::Context<'_>,
+        buf: &mut [u8],
+    ) -> ::core::task::Poll<::std::io::Result<usize>> {
+        unsafe {
+            match self.get_unchecked_mut() {
+                Enum::A(x) => {
+                    ::futures::io::AsyncRead::poll_read(
+                        ::core::pin::Pin::new_unchecked(x),
+                        cx,
+                        buf,
+                    )
+                }
+                Enum::B(x) => {
+                    ::futures::io::AsyncRead::poll_read(
+                        ::core::pin::Pin::new_unchecked(x),
+                        cx,
+                        buf,
+                    )
+                }
+            }
+        }
+    }
+    #[inline]
+    fn poll_read_vectored(
+        self: ::core::pin::Pin<&mut Self>,
+        cx: &mut ::core::task::Context<'_>,
+        bufs: &mut [::std::io::IoSliceMut<'_>],
+    ) -> ::core::task::Poll<::std::io::Result<usize>> {
+        unsafe {
+            match self.get_unchecked_mut() {
+                Enum::A(x) => {
+                    ::futures::io::AsyncRead::poll_read_vectored(
+                        ::core::pin::Pin::new_unchecked(x),
+                        cx,
+                        bufs,
+                    )
+                }
+                Enum::B(x) => {
+                    ::futures::io::AsyncRead::poll_read_vectored(
+                        ::core::pin::Pin::new_unchecked(x),
+                        cx,
+                        bufs,
+                    )
+                }
+            }
+        }
+    }
+}
+/// A reader that wraps [`Enum`] and yields to the executor after a number of consecutive `Ready` results, created by `EnumBudgetedReader::new`.
+#[allow(dead_code)]
+struct EnumBudgetedReader<A, B> {
+    inner: Enum<A, B>,
+    ready: usize,
+}
+#[allow(dead_code)]
+impl<A, B> EnumBudgetedReader<A, B> {
+    /// Creates a new `EnumBudgetedReader` that wraps `inner`.
+    fn new(inner: Enum<A, B>) -> Self {
+        Self { inner, ready: 0 }
+    }
+    /// Returns a reference to the wrapped enum.
+    fn get_ref(&self) -> &Enum<A, B> {
+        &self.inner
+    }
+    /// Returns a mutable reference to the wrapped enum.
+    fn get_mut(&mut self) -> &mut Enum<A, B> {
+        &mut self.inner
+    }
+    /// Returns the wrapped enum.
+    fn into_inner(self) -> Enum<A, B> {
+        self.inner
+    }
+}
+#[allow(unsafe_code)]
+impl<A, B> ::futures::io::AsyncRead for EnumBudgetedReader<A, B>
+where
+    Enum<A, B>: ::futures::io::AsyncRead,
+{
+    #[inline]
+    fn poll_read(
+        self: ::core::pin::Pin<&mut Self>,
+        cx: &mut ::core::task::Context<'_>,
+        buf: &mut [u8],
+    ) -> ::core::task::Poll<::std::io::Result<usize>> {
+        let this = unsafe { self.get_unchecked_mut() };
+        if this.ready >= 128usize {
+            this.ready = 0;
+            cx.waker().wake_by_ref();
+            return ::core::task::Poll::Pending;
+        }
+        let poll = unsafe {
+            <Enum<
+                A,
+                B,
+            > as ::futures::io::AsyncRead>::poll_read(
+                ::core::pin::Pin::new_unchecked(&mut this.inner),
+                cx,
+                buf,
+            )
+        };
+        this.ready = if poll.is_ready() { this.ready + 1 } else { 0 };
+        poll
+    }
+    #[inline]
+    fn poll_read_vectored(
+        self: ::core::pin::Pin<&mut Self>,
+        cx: &mut ::core::task::Context<'_>,
+        bufs: &mut [::std::io::IoSliceMut<'_>],
+    ) -> ::core::task::Poll<::std::io::Result<usize>> {
+        let this = unsafe { self.get_unchecked_mut() };
+        if this.ready >= 128usize {
+            this.ready = 0;
+            cx.waker().wake_by_ref();
+            return ::core::task::Poll::Pending;
+        }
+        let poll = unsafe {
+            <Enum<
+                A,
+                B,
+            > as ::futures::io::AsyncRead>::poll_read_vectored(
+                ::core::pin::Pin::new_unchecked(&mut this.inner),
+                cx,
+                bufs,
+            )
+        };
+        this.ready = if poll.is_ready() { this.ready + 1 } else { 0 };
+        poll
+    }
+}
+#[allow(unsafe_code)]
+impl<A, B> ::futures::io::AsyncBufRead for Enum<A, B>
+where
+    A: ::futures::io::AsyncBufRead,
+    B: ::futures::io::AsyncBufRead,
+{
+    #[inline]
+    fn poll_fill_buf<'__a>(
+        self: ::core::pin::Pin<&'__a mut Self>,
+        cx: &mut ::core::task::Context<'_>,
+    ) -> ::core::task::Poll<::std::io::Result<&'__a [u8]>> {
+        unsafe {
+            match self.get_unchecked_mut() {
+                Enum::A(x) => {
+                    ::futures::io::AsyncBufRead::poll_fill_buf(
+                        ::core::pin::Pin::new_unchecked(x),
+                        cx,
+                    )
+                }
+                Enum::B(x) => {
+                    ::futures::io::AsyncBufRead::poll_fill_buf(
+                        ::core::pin::Pin::new_unchecked(x),
+                        cx,
+                    )
+                }
+            }
+        }
+    }
+    #[inline]
+    fn consume(self: ::core::pin::Pin<&mut Self>, amt: usize) {
+        unsafe {
+            match self.get_unchecked_mut() {
+                Enum::A(x) => {
+                    ::futures::io::AsyncBufRead::consume(
+                        ::core::pin::Pin::new_unchecked(x),
+                        amt,
+                    )
+                }
+                Enum::B(x) => {
+                    ::futures::io::AsyncBufRead::consume(
+                        ::core::pin::Pin::new_unchecked(x),
+                        amt,
+                    )
+                }
+            }
+        }
+    }
+}
+#[allow(unsafe_code)]
+impl<A, B> ::futures::io::AsyncBufRead for EnumBudgetedReader<A, B>
+where
+    Enum<A, B>: ::futures::io::AsyncBufRead,
+{
+    #[inline]
+    fn poll_fill_buf<'__a>(
+        self: ::core::pin::Pin<&'__a mut Self>,
+        cx: &mut ::core::task::Context<'_>,
+    ) -> ::core::task::Poll<::std::io::Result<&'__a [u8]>> {
+        let this = unsafe { self.get_unchecked_mut() };
+        if this.ready >= 128usize {
+            this.ready = 0;
+            cx.waker().wake_by_ref();
+            return ::core::task::Poll::Pending;
+        }
+        let poll = unsafe {
+            <Enum<
+                A,
+                B,
+            > as ::futures::io::AsyncBufRead>::poll_fill_buf(
+                ::core::pin::Pin::new_unchecked(&mut this.inner),
+                cx,
+            )
+        };
+        this.ready = if poll.is_ready() { this.ready + 1 } else { 0 };
+        poll
+    }
+    #[inline]
+    fn consume(self: ::core::pin::Pin<&mut Self>, amt: usize) {
+        let this = unsafe { self.get_unchecked_mut() };
+        unsafe {
+            <Enum<
+                A,
+                B,
+            > as ::futures::io::AsyncBufRead>::consume(
+                ::core::pin::Pin::new_unchecked(&mut this.inner),
+                amt,
+            )
+        }
+    }
+}
+#[futures_enum(budget = 2)]
+enum Borrowed<'__a, A> {
+    A(A),
+    B(&'__a [u8]),
+}
+#[allow(unsafe_code)]
+impl<'__a, A> ::futures::io::AsyncRead for Borrowed<'__a, A>
+where
+    A: ::futures::io::AsyncRead,
+    &'__a [u8]: ::futures::io::AsyncRead,
+{
+    #[inline]
+    fn poll_read(
+        self: ::core::pin::Pin<&mut Self>,
+        cx: &mut ::core::task::Context<'_>,
+        buf: &mut [u8],
+    ) -> ::core::task::Poll<::std::io::Result<usize>> {
+        unsafe {
+            match self.get_unchecked_mut() {
+                Borrowed::A(x) => {
+                    ::futures::io::AsyncRead::poll_read(
+                        ::core::pin::Pin::new_unchecked(x),
+                        cx,
+                        buf,
+                    )
+                }
+                Borrowed::B(x) => {
+                    ::futures::io::AsyncRead::poll_read(
+                        ::core::pin::Pin::new_unchecked(x),
+                        cx,
+                        buf,
+                    )
+                }
+            }
+        }
+    }
+    #[inline]
+    fn poll_read_vectored(
+        self: ::core::pin::Pin<&mut Self>,
+        cx: &mut ::core::task::Context<'_>,
+        bufs: &mut [::std::io::IoSliceMut<'_>],
+    ) -> ::core::task::Poll<::std::io::Result<usize>> {
+        unsafe {
+            match self.get_unchecked_mut() {
+                Borrowed::A(x) => {
+                    ::futures::io::AsyncRead::poll_read_vectored(
+                        ::core::pin::Pin::new_unchecked(x),
+                        cx,
+                        bufs,
+                    )
+                }
+                Borrowed::B(x) => {
+                    ::futures::io::AsyncRead::poll_read_vectored(
+                        ::core::pin::Pin::new_unchecked(x),
+                        cx,
+                        bufs,
+                    )
+                }
+            }
+        }
+    }
+}
+/// A reader that wraps [`Borrowed`] and yields to the executor after a number of consecutive `Ready` results, created by `BorrowedBudgetedReader::new`.
+#[allow(dead_code)]
+struct BorrowedBudgetedReader<'__a, A> {
+    inner: Borrowed<'__a, A>,
+    ready: usize,
+}
+#[allow(dead_code)]
+impl<'__a, A> BorrowedBudgetedReader<'__a, A> {
+    /// Creates a new `BorrowedBudgetedReader` that wraps `inner`.
+    fn new(inner: Borrowed<'__a, A>) -> Self {
+        Self { inner, ready: 0 }
+    }
+    /// Returns a reference to the wrapped enum.
+    fn get_ref(&self) -> &Borrowed<'__a, A> {
+        &self.inner
+    }
+    /// Returns a mutable reference to the wrapped enum.
+    fn get_mut(&mut self) -> &mut Borrowed<'__a, A> {
+        &mut self.inner
+    }
+    /// Returns the wrapped enum.
+    fn into_inner(self) -> Borrowed<'__a, A> {
+        self.inner
+    }
+}
+#[allow(unsafe_code)]
+impl<'__a, A> ::futures::io::AsyncRead for BorrowedBudgetedReader<'__a, A>
+where
+    Borrowed<'__a, A>: ::futures::io::AsyncRead,
+{
+    #[inline]
+    fn poll_read(
+        self: ::core::pin::Pin<&mut Self>,
+        cx: &mut ::core::task::Context<'_>,
+        buf: &mut [u8],
+    ) -> ::core::task::Poll<::std::io::Result<usize>> {
+        let this = unsafe { self.get_unchecked_mut() };
+        if this.ready >= 2usize {
+            this.ready = 0;
+            cx.waker().wake_by_ref();
+            return ::core::task::Poll::Pending;
+        }
+        let poll = unsafe {
+            <Borrowed<
+                '__a,
+                A,
+            > as ::futures::io::AsyncRead>::poll_read(
+                ::core::pin::Pin::new_unchecked(&mut this.inner),
+                cx,
+                buf,
+            )
+        };
+        this.ready = if poll.is_ready() { this.ready + 1 } else { 0 };
+        poll
+    }
+    #[inline]
+    fn poll_read_vectored(
+        self: ::core::pin::Pin<&mut Self>,
+        cx: &mut ::core::task::Context<'_>,
+        bufs: &mut [::std::io::IoSliceMut<'_>],
+    ) -> ::core::task::Poll<::std::io::Result<usize>> {
+        let this = unsafe { self.get_unchecked_mut() };
+        if this.ready >= 2usize {
+            this.ready = 0;
+            cx.waker().wake_by_ref();
+            return ::core::task::Poll::Pending;
+        }
+        let poll = unsafe {
+            <Borrowed<
+                '__a,
+                A,
+            > as ::futures::io::AsyncRead>::poll_read_vectored(
+                ::core::pin::Pin::new_unchecked(&mut this.inner),
+                cx,
+                bufs,
+            )
+        };
+        this.ready = if poll.is_ready() { this.ready + 1 } else { 0 };
+        poll
+    }
+}
+#[allow(unsafe_code)]
+impl<'__a, A> ::futures::io::AsyncBufRead for Borrowed<'__a, A>
+where
+    A: ::futures::io::AsyncBufRead,
+    &'__a [u8]: ::futures::io::AsyncBufRead,
+{
+    #[inline]
+    fn poll_fill_buf<'__a1>(
+        self: ::core::pin::Pin<&'__a1 mut Self>,
+        cx: &mut ::core::task::Context<'_>,
+    ) -> ::core::task::Poll<::std::io::Result<&'__a1 [u8]>> {
+        unsafe {
+            match self.get_unchecked_mut() {
+                Borrowed::A(x) => {
+                    ::futures::io::AsyncBufRead::poll_fill_buf(
+                        ::core::pin::Pin::new_unchecked(x),
+                        cx,
+                    )
+                }
+                Borrowed::B(x) => {
+                    ::futures::io::AsyncBufRead::poll_fill_buf(
+                        ::core::pin::Pin::new_unchecked(x),
+                        cx,
+                    )
+                }
+            }
+        }
+    }
+    #[inline]
+    fn consume(self: ::core::pin::Pin<&mut Self>, amt: usize) {
+        unsafe {
+            match self.get_unchecked_mut() {
+                Borrowed::A(x) => {
+                    ::futures::io::AsyncBufRead::consume(
+                        ::core::pin::Pin::new_unchecked(x),
+                        amt,
+                    )
+                }
+                Borrowed::B(x) => {
+                    ::futures::io::AsyncBufRead::consume(
+                        ::core::pin::Pin::new_unchecked(x),
+                        amt,
+                    )
+                }
+            }
+        }
+    }
+}
+#[allow(unsafe_code)]
+impl<'__a, A> ::futures::io::AsyncBufRead for BorrowedBudgetedReader<'__a, A>
+where
+    Borrowed<'__a, A>: ::futures::io::AsyncBufRead,
+{
+    #[inline]
+    fn poll_fill_buf<'__a1>(
+        self: ::core::pin::Pin<&'__a1 mut Self>,
+        cx: &mut ::core::task::Context<'_>,
+    ) -> ::core::task::Poll<::std::io::Result<&'__a1 [u8]>> {
+        let this = unsafe { self.get_unchecked_mut() };
+        if this.ready >= 2usize {
+            this.ready = 0;
+            cx.waker().wake_by_ref();
+            return ::core::task::Poll::Pending;
+        }
+        let poll = unsafe {
+            <Borrowed<
+                '__a,
+                A,
+            > as ::futures::io::AsyncBufRead>::poll_fill_buf(
+                ::core::pin::Pin::new_unchecked(&mut this.inner),
+                cx,
+            )
+        };
+        this.ready = if poll.is_ready() { this.ready + 1 } else { 0 };
+        poll
+    }
+    #[inline]
+    fn consume(self: ::core::pin::Pin<&mut Self>, amt: usize) {
+        let this = unsafe { self.get_unchecked_mut() };
+        unsafe {
+            <Borrowed<
+                '__a,
+                A,
+            > as ::futures::io::AsyncBufRead>::consume(
+                ::core::pin::Pin::new_unchecked(&mut this.inner),
+                amt,
+            )
+        }
+    }
+}
+fn main() {}
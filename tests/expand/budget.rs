@@ -0,0 +1,20 @@
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use futures_enum::*;
+
+#[derive(Stream, AsyncRead, AsyncBufRead)]
+#[futures_enum(budget = 128)]
+enum Enum<A, B> {
+    A(A),
+    B(B),
+}
+
+// The lifetime of `AsyncBufRead::poll_fill_buf` is renamed.
+#[derive(AsyncRead, AsyncBufRead)]
+#[futures_enum(budget = 2)]
+enum Borrowed<'__a, A> {
+    A(A),
+    B(&'__a [u8]),
+}
+
+fn main() {}
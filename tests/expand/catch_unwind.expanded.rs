@@ -0,0 +1,233 @@
+use std::any::Any;
+use futures_enum::*;
+struct Panic;
+impl Panic {
+    fn new(_variant: &'static str, _payload: Box<dyn Any + Send>) -> Self {
+        Self
+    }
+}
+#[futures_enum(catch_unwind = "Panic")]
+enum Enum<A, B> {
+    A(A),
+    B(B),
+}
+const _: () = {
+    #[diagnostic::on_unimplemented(
+        message = "variant `B` has `Output = {U}`, but variant `A` has `Output = {T}`",
+        label = "expected `Output = {T}`",
+        note = "all variants must have the same `Output`"
+    )]
+    trait __AssertOutput1<
+        T: ?::core::marker::Sized,
+        U: ?::core::marker::Sized,
+    >: ::core::future::Future<Output = T> {}
+    #[diagnostic::do_not_recommend]
+    impl<F, T: ?::core::marker::Sized> __AssertOutput1<T, T> for F
+    where
+        F: ?::core::marker::Sized + ::core::future::Future<Output = T>,
+    {}
+    #[allow(unsafe_code)]
+    impl<A, B> ::core::future::Future for Enum<A, B>
+    where
+        A: ::core::future::Future,
+        B: ::core::future::Future,
+        B: __AssertOutput1<
+            <A as ::core::future::Future>::Output,
+            <B as ::core::future::Future>::Output,
+        >,
+    {
+        type Output = ::core::result::Result<
+            <A as ::core::future::Future>::Output,
+            Panic,
+        >;
+        #[inline]
+        fn poll(
+            self: ::core::pin::Pin<&mut Self>,
+            cx: &mut ::core::task::Context<'_>,
+        ) -> ::core::task::Poll<Self::Output> {
+            let variant = match &*self {
+                Enum::A(_) => "A",
+                Enum::B(_) => "B",
+            };
+            match ::std::panic::catch_unwind(
+                ::std::panic::AssertUnwindSafe(move || {
+                    unsafe {
+                        match self.get_unchecked_mut() {
+                            Enum::A(x) => {
+                                ::core::future::Future::poll(
+                                    ::core::pin::Pin::new_unchecked(x),
+                                    cx,
+                                )
+                            }
+                            Enum::B(x) => {
+                                ::core::future::Future::poll(
+                                    ::core::pin::Pin::new_unchecked(x),
+                                    cx,
+                                )
+                            }
+                        }
+                    }
+                }),
+            ) {
+                ::core::result::Result::Ok(::core::task::Poll::Ready(x)) => {
+                    ::core::task::Poll::Ready(::core::result::Result::Ok(x))
+                }
+                ::core::result::Result::Err(payload) => {
+                    ::core::task::Poll::Ready(
+                        ::core::result::Result::Err(Panic::new(variant, payload)),
+                    )
+                }
+                ::core::result::Result::Ok(::core::task::Poll::Pending) => {
+                    ::core::task::Poll::Pending
+                }
+            }
+        }
+    }
+};
+const _: () = {
+    #[diagnostic::on_unimplemented(
+        message = "variant `B` has `Item = {U}`, but variant `A` has `Item = {T}`",
+        label = "expected `Item = {T}`",
+        note = "all variants must have the same `Item`"
+    )]
+    trait __AssertItem1<
+        T: ?::core::marker::Sized,
+        U: ?::core::marker::Sized,
+    >: ::futures::stream::Stream<Item = T> {}
+    #[diagnostic::do_not_recommend]
+    impl<F, T: ?::core::marker::Sized> __AssertItem1<T, T> for F
+    where
+        F: ?::core::marker::Sized + ::futures::stream::Stream<Item = T>,
+    {}
+    #[allow(unsafe_code)]
+    impl<A, B> ::futures::stream::Stream for Enum<A, B>
+    where
+        A: ::futures::stream::Stream,
+        B: ::futures::stream::Stream,
+        B: __AssertItem1<
+            <A as ::futures::stream::Stream>::Item,
+            <B as ::futures::stream::Stream>::Item,
+        >,
+    {
+        type Item = <A as ::futures::stream::Stream>::Item;
+        #[inline]
+        fn poll_next(
+            self: ::core::pin::Pin<&mut Self>,
+            cx: &mut ::core::task::Context<'_>,
+        ) -> ::core::task::Poll<::core::option::Option<Self::Item>> {
+            unsafe {
+                match self.get_unchecked_mut() {
+                    Enum::A(x) => {
+                        ::futures::stream::Stream::poll_next(
+                            ::core::pin::Pin::new_unchecked(x),
+                            cx,
+                        )
+                    }
+                    Enum::B(x) => {
+                        ::futures::stream::Stream::poll_next(
+                            ::core::pin::Pin::new_unchecked(x),
+                            cx,
+                        )
+                    }
+                }
+            }
+        }
+        #[inline]
+        fn size_hint(&self) -> (usize, ::core::option::Option<usize>) {
+            match self {
+                Enum::A(x) => ::futures::stream::Stream::size_hint(x),
+                Enum::B(x) => ::futures::stream::Stream::size_hint(x),
+            }
+        }
+    }
+};
+/// A stream that wraps [`Enum`] and returns panics in its variants as errors, created by `EnumCatchUnwind::new`.
+#[allow(dead_code)]
+struct EnumCatchUnwind<A, B> {
+    inner: Enum<A, B>,
+    panicked: bool,
+}
+#[allow(dead_code)]
+impl<A, B> EnumCatchUnwind<A, B> {
+    /// Creates a new `EnumCatchUnwind` that wraps `inner`.
+    fn new(inner: Enum<A, B>) -> Self {
+        Self { inner, panicked: false }
+    }
+    /// Returns a reference to the wrapped enum.
+    fn get_ref(&self) -> &Enum<A, B> {
+        &self.inner
+    }
+    /// Returns a mutable reference to the wrapped enum.
+    fn get_mut(&mut self) -> &mut Enum<A, B> {
+        &mut self.inner
+    }
+    /// Returns the wrapped enum.
+    fn into_inner(self) -> Enum<A, B> {
+        self.inner
+    }
+}
+#[allow(unsafe_code)]
+impl<A, B> ::futures::stream::Stream for EnumCatchUnwind<A, B>
+where
+    Enum<A, B>: ::futures::stream::Stream,
+{
+    type Item = ::core::result::Result<
+        <Enum<A, B> as ::futures::stream::Stream>::Item,
+        Panic,
+    >;
+    #[inline]
+    fn poll_next(
+        self: ::core::pin::Pin<&mut Self>,
+        cx: &mut ::core::task::Context<'_>,
+    ) -> ::core::task::Poll<::core::option::Option<Self::Item>> {
+        let this = unsafe { self.get_unchecked_mut() };
+        if this.panicked {
+            return ::core::task::Poll::Ready(::core::option::Option::None);
+        }
+        let variant = match &this.inner {
+            Enum::A(_) => "A",
+            Enum::B(_) => "B",
+        };
+        let f = ::std::panic::AssertUnwindSafe(|| unsafe {
+            <Enum<
+                A,
+                B,
+            > as ::futures::stream::Stream>::poll_next(
+                ::core::pin::Pin::new_unchecked(&mut this.inner),
+                cx,
+            )
+        });
+        match ::std::panic::catch_unwind(f) {
+            ::core::result::Result::Ok(
+                ::core::task::Poll::Ready(::core::option::Option::Some(x)),
+            ) => {
+                ::core::task::Poll::Ready(
+                    ::core::option::Option::Some(::core::result::Result::Ok(x)),
+                )
+            }
+            ::core::result::Result::Ok(
+                ::core::task::Poll::Ready(::core::option::Option::None),
+            ) => ::core::task::Poll::Ready(::core::option::Option::None),
+            ::core::result::Result::Ok(::core::task::Poll::Pending) => {
+                ::core::task::Poll::Pending
+            }
+            ::core::result::Result::Err(payload) => {
+                this.panicked = true;
+                ::core::task::Poll::Ready(
+                    ::core::option::Option::Some(
+                        ::core::result::Result::Err(Panic::new(variant, payload)),
+                    ),
+                )
+            }
+        }
+    }
+    #[inline]
+    fn size_hint(&self) -> (usize, ::core::option::Option<usize>) {
+        if self.panicked {
+            (0, ::core::option::Option::Some(0))
+        } else {
+            <Enum<A, B> as ::futures::stream::Stream>::size_hint(&self.inner)
+        }
+    }
+}
+fn main() {}
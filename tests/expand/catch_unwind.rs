@@ -0,0 +1,22 @@
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use std::any::Any;
+
+use futures_enum::*;
+
+struct Panic;
+
+impl Panic {
+    fn new(_variant: &'static str, _payload: Box<dyn Any + Send>) -> Self {
+        Self
+    }
+}
+
+#[derive(Future, Stream)]
+#[futures_enum(catch_unwind = "Panic")]
+enum Enum<A, B> {
+    A(A),
+    B(B),
+}
+
+fn main() {}
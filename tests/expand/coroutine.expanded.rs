@@ -0,0 +1,80 @@
+#![feature(coroutine_trait)]
+use futures_enum::*;
+enum Enum<A, B> {
+    A(A),
+    B(B),
+}
+const _: () = {
+    #[diagnostic::on_unimplemented(
+        message = "variant `B` has `Yield = {U}`, but variant `A` has `Yield = {T}`",
+        label = "expected `Yield = {T}`",
+        note = "all variants must have the same `Yield`"
+    )]
+    trait __AssertYield1<
+        __R,
+        T: ?::core::marker::Sized,
+        U: ?::core::marker::Sized,
+    >: ::core::ops::Coroutine<__R, Yield = T> {}
+    #[diagnostic::do_not_recommend]
+    impl<F, __R, T: ?::core::marker::Sized> __AssertYield1<__R, T, T> for F
+    where
+        F: ?::core::marker::Sized + ::core::ops::Coroutine<__R, Yield = T>,
+    {}
+    #[diagnostic::on_unimplemented(
+        message = "variant `B` has `Return = {U}`, but variant `A` has `Return = {T}`",
+        label = "expected `Return = {T}`",
+        note = "all variants must have the same `Return`"
+    )]
+    trait __AssertReturn1<
+        __R,
+        T: ?::core::marker::Sized,
+        U: ?::core::marker::Sized,
+    >: ::core::ops::Coroutine<__R, Return = T> {}
+    #[diagnostic::do_not_recommend]
+    impl<F, __R, T: ?::core::marker::Sized> __AssertReturn1<__R, T, T> for F
+    where
+        F: ?::core::marker::Sized + ::core::ops::Coroutine<__R, Return = T>,
+    {}
+    #[allow(unsafe_code)]
+    impl<A, B, __R> ::core::ops::Coroutine<__R> for Enum<A, B>
+    where
+        A: ::core::ops::Coroutine<__R>,
+        B: ::core::ops::Coroutine<__R>,
+        B: __AssertYield1<
+            __R,
+            <A as ::core::ops::Coroutine<__R>>::Yield,
+            <B as ::core::ops::Coroutine<__R>>::Yield,
+        >,
+        B: __AssertReturn1<
+            __R,
+            <A as ::core::ops::Coroutine<__R>>::Return,
+            <B as ::core::ops::Coroutine<__R>>::Return,
+        >,
+    {
+        type Yield = <A as ::core::ops::Coroutine<__R>>::Yield;
+        type Return = <A as ::core::ops::Coroutine<__R>>::Return;
+        #[inline]
+        fn resume(
+            self: ::core::pin::Pin<&mut Self>,
+            arg: __R,
+        ) -> ::core::ops::CoroutineState<Self::Yield, Self::Return> {
+            unsafe {
+                match self.get_unchecked_mut() {
+                    Enum::A(x) => {
+                        ::core::ops::Coroutine::resume(
+                            ::core::pin::Pin::new_unchecked(x),
+                            arg,
+                        )
+                    }
+                    Enum::B(x) => {
+                        ::core::ops::Coroutine::resume(
+                            ::core::pin::Pin::new_unchecked(x),
+                            arg,
+                        )
+                    }
+                }
+            }
+        }
+    }
+};
+fn main() {}
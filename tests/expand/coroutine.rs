@@ -0,0 +1,13 @@
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+#![feature(coroutine_trait)]
+
+use futures_enum::*;
+
+#[derive(Coroutine)]
+enum Enum<A, B> {
+    A(A),
+    B(B),
+}
+
+fn main() {}
@@ -0,0 +1,239 @@
+use futures_enum::*;
+#[futures_enum(crate = "my_facade::futures", sink = "my_facade::sink")]
+enum Enum<A, B> {
+    A(A),
+    B(B),
+}
+const _: () = {
+    #[diagnostic::on_unimplemented(
+        message = "variant `B` has `Item = {U}`, but variant `A` has `Item = {T}`",
+        label = "expected `Item = {T}`",
+        note = "all variants must have the same `Item`"
+    )]
+    trait __AssertItem1<
+        T: ?::core::marker::Sized,
+        U: ?::core::marker::Sized,
+    >: my_facade::futures::stream::Stream<Item = T> {}
+    #[diagnostic::do_not_recommend]
+    impl<F, T: ?::core::marker::Sized> __AssertItem1<T, T> for F
+    where
+        F: ?::core::marker::Sized + my_facade::futures::stream::Stream<Item = T>,
+    {}
+    #[allow(unsafe_code)]
+    impl<A, B> my_facade::futures::stream::Stream for Enum<A, B>
+    where
+        A: my_facade::futures::stream::Stream,
+        B: my_facade::futures::stream::Stream,
+        B: __AssertItem1<
+            <A as my_facade::futures::stream::Stream>::Item,
+            <B as my_facade::futures::stream::Stream>::Item,
+        >,
+    {
+        type Item = <A as my_facade::futures::stream::Stream>::Item;
+        #[inline]
+        fn poll_next(
+            self: ::core::pin::Pin<&mut Self>,
+            cx: &mut ::core::task::Context<'_>,
+        ) -> ::core::task::Poll<::core::option::Option<Self::Item>> {
+            unsafe {
+                match self.get_unchecked_mut() {
+                    Enum::A(x) => {
+                        my_facade::futures::stream::Stream::poll_next(
+                            ::core::pin::Pin::new_unchecked(x),
+                            cx,
+                        )
+                    }
+                    Enum::B(x) => {
+                        my_facade::futures::stream::Stream::poll_next(
+                            ::core::pin::Pin::new_unchecked(x),
+                            cx,
+                        )
+                    }
+                }
+            }
+        }
+        #[inline]
+        fn size_hint(&self) -> (usize, ::core::option::Option<usize>) {
+            match self {
+                Enum::A(x) => my_facade::futures::stream::Stream::size_hint(x),
+                Enum::B(x) => my_facade::futures::stream::Stream::size_hint(x),
+            }
+        }
+    }
+};
+const _: () = {
+    #[diagnostic::on_unimplemented(
+        message = "variant `B` has `Error = {U}`, but variant `A` has `Error = {T}`",
+        label = "expected `Error = {T}`",
+        note = "all variants must have the same `Error`"
+    )]
+    trait __AssertError1<
+        __Item,
+        T: ?::core::marker::Sized,
+        U: ?::core::marker::Sized,
+    >: my_facade::sink::Sink<__Item, Error = T> {}
+    #[diagnostic::do_not_recommend]
+    impl<F, __Item, T: ?::core::marker::Sized> __AssertError1<__Item, T, T> for F
+    where
+        F: ?::core::marker::Sized + my_facade::sink::Sink<__Item, Error = T>,
+    {}
+    #[allow(unsafe_code)]
+    impl<A, B, __Item> my_facade::sink::Sink<__Item> for Enum<A, B>
+    where
+        A: my_facade::sink::Sink<__Item>,
+        B: my_facade::sink::Sink<__Item>,
+        B: __AssertError1<
+            __Item,
+            <A as my_facade::sink::Sink<__Item>>::Error,
+            <B as my_facade::sink::Sink<__Item>>::Error,
+        >,
+    {
+        type Error = <A as my_facade::sink::Sink<__Item>>::Error;
+        #[inline]
+        fn poll_ready(
+            self: ::core::pin::Pin<&mut Self>,
+            cx: &mut ::core::task::Context<'_>,
+        ) -> ::core::task::Poll<::core::result::Result<(), Self::Error>> {
+            unsafe {
+                match self.get_unchecked_mut() {
+                    Enum::A(x) => {
+                        my_facade::sink::Sink::poll_ready(
+                            ::core::pin::Pin::new_unchecked(x),
+                            cx,
+                        )
+                    }
+                    Enum::B(x) => {
+                        my_facade::sink::Sink::poll_ready(
+                            ::core::pin::Pin::new_unchecked(x),
+                            cx,
+                        )
+                    }
+                }
+            }
+        }
+        #[inline]
+        fn start_send(
+            self: ::core::pin::Pin<&mut Self>,
+            item: __Item,
+        ) -> ::core::result::Result<(), Self::Error> {
+            unsafe {
+                match self.get_unchecked_mut() {
+                    Enum::A(x) => {
+                        my_facade::sink::Sink::start_send(
+                            ::core::pin::Pin::new_unchecked(x),
+                            item,
+                        )
+                    }
+                    Enum::B(x) => {
+                        my_facade::sink::Sink::start_send(
+                            ::core::pin::Pin::new_unchecked(x),
+                            item,
+                        )
+                    }
+                }
+            }
+        }
+        #[inline]
+        fn poll_flush(
+            self: ::core::pin::Pin<&mut Self>,
+            cx: &mut ::core::task::Context<'_>,
+        ) -> ::core::task::Poll<::core::result::Result<(), Self::Error>> {
+            unsafe {
+                match self.get_unchecked_mut() {
+                    Enum::A(x) => {
+                        my_facade::sink::Sink::poll_flush(
+                            ::core::pin::Pin::new_unchecked(x),
+                            cx,
+                        )
+                    }
+                    Enum::B(x) => {
+                        my_facade::sink::Sink::poll_flush(
+                            ::core::pin::Pin::new_unchecked(x),
+                            cx,
+                        )
+                    }
+                }
+            }
+        }
+        #[inline]
+        fn poll_close(
+            self: ::core::pin::Pin<&mut Self>,
+            cx: &mut ::core::task::Context<'_>,
+        ) -> ::core::task::Poll<::core::result::Result<(), Self::Error>> {
+            unsafe {
+                match self.get_unchecked_mut() {
+                    Enum::A(x) => {
+                        my_facade::sink::Sink::poll_close(
+                            ::core::pin::Pin::new_unchecked(x),
+                            cx,
+                        )
+                    }
+                    Enum::B(x) => {
+                        my_facade::sink::Sink::poll_close(
+                            ::core::pin::Pin::new_unchecked(x),
+                            cx,
+                        )
+                    }
+                }
+            }
+        }
+    }
+};
+#[allow(unsafe_code)]
+impl<A, B> my_facade::futures::io::AsyncRead for Enum<A, B>
+where
+    A: my_facade::futures::io::AsyncRead,
+    B: my_facade::futures::io::AsyncRead,
+{
+    #[inline]
+    fn poll_read(
+        self: ::core::pin::Pin<&mut Self>,
+        cx: &mut ::core::task::Context<'_>,
+        buf: &mut [u8],
+    ) -> ::core::task::Poll<::std::io::Result<usize>> {
+        unsafe {
+            match self.get_unchecked_mut() {
+                Enum::A(x) => {
+                    my_facade::futures::io::AsyncRead::poll_read(
+                        ::core::pin::Pin::new_unchecked(x),
+                        cx,
+                        buf,
+                    )
+                }
+                Enum::B(x) => {
+                    my_facade::futures::io::AsyncRead::poll_read(
+                        ::core::pin::Pin::new_unchecked(x),
+                        cx,
+                        buf,
+                    )
+                }
+            }
+        }
+    }
+    #[inline]
+    fn poll_read_vectored(
+        self: ::core::pin::Pin<&mut Self>,
+        cx: &mut ::core::task::Context<'_>,
+        bufs: &mut [::std::io::IoSliceMut<'_>],
+    ) -> ::core::task::Poll<::std::io::Result<usize>> {
+        unsafe {
+            match self.get_unchecked_mut() {
+                Enum::A(x) => {
+                    my_facade::futures::io::AsyncRead::poll_read_vectored(
+                        ::core::pin::Pin::new_unchecked(x),
+                        cx,
+                        bufs,
+                    )
+                }
+                Enum::B(x) => {
+                    my_facade::futures::io::AsyncRead::poll_read_vectored(
+                        ::core::pin::Pin::new_unchecked(x),
+                        cx,
+                        bufs,
+                    )
+                }
+            }
+        }
+    }
+}
+fn main() {}
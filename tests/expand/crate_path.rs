@@ -0,0 +1,12 @@
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use futures_enum::*;
+
+#[derive(Stream, Sink, AsyncRead)]
+#[futures_enum(crate = "my_facade::futures", sink = "my_facade::sink")]
+enum Enum<A, B> {
+    A(A),
+    B(B),
+}
+
+fn main() {}
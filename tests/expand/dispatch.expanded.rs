@@ -0,0 +1,93 @@
+use std::future::Future;
+use futures_enum::*;
+fn foo(x: i32) -> impl Future<Output = i32> {
+    enum __FuturesEnumDispatch<__T0, __T1, __T2> {
+        __V0(__T0),
+        __V1(__T1),
+        __V2(__T2),
+    }
+    const _: () = {
+        #[diagnostic::on_unimplemented(
+            message = "variant `__V1` has `Output = {U}`, but variant `__V0` has `Output = {T}`",
+            label = "expected `Output = {T}`",
+            note = "all variants must have the same `Output`"
+        )]
+        trait __AssertOutput1<
+            T: ?::core::marker::Sized,
+            U: ?::core::marker::Sized,
+        >: ::core::future::Future<Output = T> {}
+        #[diagnostic::do_not_recommend]
+        impl<F, T: ?::core::marker::Sized> __AssertOutput1<T, T> for F
+        where
+            F: ?::core::marker::Sized + ::core::future::Future<Output = T>,
+        {}
+        #[diagnostic::on_unimplemented(
+            message = "variant `__V2` has `Output = {U}`, but variant `__V0` has `Output = {T}`",
+            label = "expected `Output = {T}`",
+            note = "all variants must have the same `Output`"
+        )]
+        trait __AssertOutput2<
+            T: ?::core::marker::Sized,
+            U: ?::core::marker::Sized,
+        >: ::core::future::Future<Output = T> {}
+        #[diagnostic::do_not_recommend]
+        impl<F, T: ?::core::marker::Sized> __AssertOutput2<T, T> for F
+        where
+            F: ?::core::marker::Sized + ::core::future::Future<Output = T>,
+        {}
+        #[allow(unsafe_code)]
+        impl<__T0, __T1, __T2> ::core::future::Future
+        for __FuturesEnumDispatch<__T0, __T1, __T2>
+        where
+            __T0: ::core::future::Future,
+            __T1: ::core::future::Future,
+            __T2: ::core::future::Future,
+            __T1: __AssertOutput1<
+                <__T0 as ::core::future::Future>::Output,
+                <__T1 as ::core::future::Future>::Output,
+            >,
+            __T2: __AssertOutput2<
+                <__T0 as ::core::future::Future>::Output,
+                <__T2 as ::core::future::Future>::Output,
+            >,
+        {
+            type Output = <__T0 as ::core::future::Future>::Output;
+            #[inline]
+            fn poll(
+                self: ::core::pin::Pin<&mut Self>,
+                cx: &mut ::core::task::Context<'_>,
+            ) -> ::core::task::Poll<Self::Output> {
+                unsafe {
+                    match self.get_unchecked_mut() {
+                        __FuturesEnumDispatch::__V0(x) => {
+                            ::core::future::Future::poll(
+                                ::core::pin::Pin::new_unchecked(x),
+                                cx,
+                            )
+                        }
+                        __FuturesEnumDispatch::__V1(x) => {
+                            ::core::future::Future::poll(
+                                ::core::pin::Pin::new_unchecked(x),
+                                cx,
+                            )
+                        }
+                        __FuturesEnumDispatch::__V2(x) => {
+                            ::core::future::Future::poll(
+                                ::core::pin::Pin::new_unchecked(x),
+                                cx,
+                            )
+                        }
+                    }
+                }
+            }
+        }
+    };
+    if x < 0 {
+        return __FuturesEnumDispatch::__V0(async { 1 });
+    }
+    match x {
+        0 => __FuturesEnumDispatch::__V1(async { 0 }),
+        _ => __FuturesEnumDispatch::__V2(async move { x }),
+    }
+}
+fn main() {}
@@ -0,0 +1,18 @@
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use std::future::Future;
+
+use futures_enum::*;
+
+#[dispatch]
+fn foo(x: i32) -> impl Future<Output = i32> {
+    if x < 0 {
+        return async { 1 };
+    }
+    match x {
+        0 => async { 0 },
+        _ => async move { x },
+    }
+}
+
+fn main() {}
@@ -0,0 +1,102 @@
+use std::future::Future;
+use futures_enum::*;
+fn foo(x: bool) -> impl Future<Output = i64> {
+    struct __FuturesEnumInto<__F, __O, __I, __E>(
+        __F,
+        ::core::marker::PhantomData<fn() -> (__O, __I, __E)>,
+    );
+    #[allow(unsafe_code)]
+    impl<__F, __O, __I, __E> ::core::future::Future
+    for __FuturesEnumInto<__F, __O, __I, __E>
+    where
+        __F: ::core::future::Future,
+        __F::Output: ::core::convert::Into<__O>,
+    {
+        type Output = __O;
+        #[inline]
+        fn poll(
+            self: ::core::pin::Pin<&mut Self>,
+            cx: &mut ::core::task::Context<'_>,
+        ) -> ::core::task::Poll<Self::Output> {
+            ::core::future::Future::poll(
+                    unsafe { self.map_unchecked_mut(|this| &mut this.0) },
+                    cx,
+                )
+                .map(::core::convert::Into::into)
+        }
+    }
+    enum __FuturesEnumDispatch<__T0, __T1> {
+        __V0(__T0),
+        __V1(__T1),
+    }
+    const _: () = {
+        #[diagnostic::on_unimplemented(
+            message = "variant `__V1` has `Output = {U}`, but variant `__V0` has `Output = {T}`",
+            label = "expected `Output = {T}`",
+            note = "all variants must have the same `Output`"
+        )]
+        trait __AssertOutput1<
+            T: ?::core::marker::Sized,
+            U: ?::core::marker::Sized,
+        >: ::core::future::Future<Output = T> {}
+        #[diagnostic::do_not_recommend]
+        impl<F, T: ?::core::marker::Sized> __AssertOutput1<T, T> for F
+        where
+            F: ?::core::marker::Sized + ::core::future::Future<Output = T>,
+        {}
+        #[allow(unsafe_code)]
+        impl<__T0, __T1> ::core::future::Future for __FuturesEnumDispatch<__T0, __T1>
+        where
+            __T0: ::core::future::Future,
+            __T1: ::core::future::Future,
+            __T1: __AssertOutput1<
+                <__T0 as ::core::future::Future>::Output,
+                <__T1 as ::core::future::Future>::Output,
+            >,
+        {
+            type Output = <__T0 as ::core::future::Future>::Output;
+            #[inline]
+            fn poll(
+                self: ::core::pin::Pin<&mut Self>,
+                cx: &mut ::core::task::Context<'_>,
+            ) -> ::core::task::Poll<Self::Output> {
+                unsafe {
+                    match self.get_unchecked_mut() {
+                        __FuturesEnumDispatch::__V0(x) => {
+                            ::core::future::Future::poll(
+                                ::core::pin::Pin::new_unchecked(x),
+                                cx,
+                            )
+                        }
+                        __FuturesEnumDispatch::__V1(x) => {
+                            ::core::future::Future::poll(
+                                ::core::pin::Pin::new_unchecked(x),
+                                cx,
+                            )
+                        }
+                    }
+                }
+            }
+        }
+    };
+    if x {
+        __FuturesEnumDispatch::__V0(
+            __FuturesEnumInto::<
+                _,
+                i64,
+                (),
+                (),
+            >(async { 1_i32 }, ::core::marker::PhantomData),
+        )
+    } else {
+        __FuturesEnumDispatch::__V1(
+            __FuturesEnumInto::<
+                _,
+                i64,
+                (),
+                (),
+            >(async { 2_u8 }, ::core::marker::PhantomData),
+        )
+    }
+}
+fn main() {}
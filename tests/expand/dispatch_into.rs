@@ -0,0 +1,12 @@
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use std::future::Future;
+
+use futures_enum::*;
+
+#[dispatch(into)]
+fn foo(x: bool) -> impl Future<Output = i64> {
+    if x { async { 1_i32 } } else { async { 2_u8 } }
+}
+
+fn main() {}
@@ -3,28 +3,54 @@ enum Enum<A, B> {
     A(A),
     B(B),
 }
-#[allow(unsafe_code)]
-impl<A, B> ::core::future::Future for Enum<A, B>
-where
-    A: ::core::future::Future,
-    B: ::core::future::Future<Output = <A as ::core::future::Future>::Output>,
-{
-    type Output = <A as ::core::future::Future>::Output;
-    #[inline]
-    fn poll(
-        self: ::core::pin::Pin<&mut Self>,
-        cx: &mut ::core::task::Context<'_>,
-    ) -> ::core::task::Poll<Self::Output> {
-        unsafe {
-            match self.get_unchecked_mut() {
-                Enum::A(x) => {
-                    ::core::future::Future::poll(::core::pin::Pin::new_unchecked(x), cx)
-                }
-                Enum::B(x) => {
-                    ::core::future::Future::poll(::core::pin::Pin::new_unchecked(x), cx)
+const _: () = {
+    #[diagnostic::on_unimplemented(
+        message = "variant `B` has `Output = {U}`, but variant `A` has `Output = {T}`",
+        label = "expected `Output = {T}`",
+        note = "all variants must have the same `Output`"
+    )]
+    trait __AssertOutput1<
+        T: ?::core::marker::Sized,
+        U: ?::core::marker::Sized,
+    >: ::core::future::Future<Output = T> {}
+    #[diagnostic::do_not_recommend]
+    impl<F, T: ?::core::marker::Sized> __AssertOutput1<T, T> for F
+    where
+        F: ?::core::marker::Sized + ::core::future::Future<Output = T>,
+    {}
+    #[allow(unsafe_code)]
+    impl<A, B> ::core::future::Future for Enum<A, B>
+    where
+        A: ::core::future::Future,
+        B: ::core::future::Future,
+        B: __AssertOutput1<
+            <A as ::core::future::Future>::Output,
+            <B as ::core::future::Future>::Output,
+        >,
+    {
+        type Output = <A as ::core::future::Future>::Output;
+        #[inline]
+        fn poll(
+            self: ::core::pin::Pin<&mut Self>,
+            cx: &mut ::core::task::Context<'_>,
+        ) -> ::core::task::Poll<Self::Output> {
+            unsafe {
+                match self.get_unchecked_mut() {
+                    Enum::A(x) => {
+                        ::core::future::Future::poll(
+                            ::core::pin::Pin::new_unchecked(x),
+                            cx,
+                        )
+                    }
+                    Enum::B(x) => {
+                        ::core::future::Future::poll(
+                            ::core::pin::Pin::new_unchecked(x),
+                            cx,
+                        )
+                    }
                 }
             }
         }
     }
-}
+};
 fn main() {}
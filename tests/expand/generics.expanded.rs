@@ -0,0 +1,232 @@
+use futures_enum::*;
+enum Enum<'__a, __Item, __Item1, const N: usize> {
+    A(&'__a mut [__Item; N]),
+    B(__Item1),
+}
+const _: () = {
+    #[diagnostic::on_unimplemented(
+        message = "variant `B` has `Item = {U}`, but variant `A` has `Item = {T}`",
+        label = "expected `Item = {T}`",
+        note = "all variants must have the same `Item`"
+    )]
+    trait __AssertItem1<
+        T: ?::core::marker::Sized,
+        U: ?::core::marker::Sized,
+    >: ::futures::stream::Stream<Item = T> {}
+    #[diagnostic::do_not_recommend]
+    impl<F, T: ?::core::marker::Sized> __AssertItem1<T, T> for F
+    where
+        F: ?::core::marker::Sized + ::futures::stream::Stream<Item = T>,
+    {}
+    #[allow(unsafe_code)]
+    impl<'__a, __Item, __Item1, const N: usize> ::futures::stream::Stream
+    for Enum<'__a, __Item, __Item1, N>
+    where
+        &'__a mut [__Item; N]: ::futures::stream::Stream,
+        __Item1: ::futures::stream::Stream,
+        __Item1: __AssertItem1<
+            <&'__a mut [__Item; N] as ::futures::stream::Stream>::Item,
+            <__Item1 as ::futures::stream::Stream>::Item,
+        >,
+    {
+        type Item = <&'__a mut [__Item; N] as ::futures::stream::Stream>::Item;
+        #[inline]
+        fn poll_next(
+            self: ::core::pin::Pin<&mut Self>,
+            cx: &mut ::core::task::Context<'_>,
+        ) -> ::core::task::Poll<::core::option::Option<Self::Item>> {
+            unsafe {
+                match self.get_unchecked_mut() {
+                    Enum::A(x) => {
+                        ::futures::stream::Stream::poll_next(
+                            ::core::pin::Pin::new_unchecked(x),
+                            cx,
+                        )
+                    }
+                    Enum::B(x) => {
+                        ::futures::stream::Stream::poll_next(
+                            ::core::pin::Pin::new_unchecked(x),
+                            cx,
+                        )
+                    }
+                }
+            }
+        }
+        #[inline]
+        fn size_hint(&self) -> (usize, ::core::option::Option<usize>) {
+            match self {
+                Enum::A(x) => ::futures::stream::Stream::size_hint(x),
+                Enum::B(x) => ::futures::stream::Stream::size_hint(x),
+            }
+        }
+    }
+};
+const _: () = {
+    #[diagnostic::on_unimplemented(
+        message = "variant `B` has `Error = {U}`, but variant `A` has `Error = {T}`",
+        label = "expected `Error = {T}`",
+        note = "all variants must have the same `Error`"
+    )]
+    trait __AssertError1<
+        __Item2,
+        T: ?::core::marker::Sized,
+        U: ?::core::marker::Sized,
+    >: ::futures::sink::Sink<__Item2, Error = T> {}
+    #[diagnostic::do_not_recommend]
+    impl<F, __Item2, T: ?::core::marker::Sized> __AssertError1<__Item2, T, T> for F
+    where
+        F: ?::core::marker::Sized + ::futures::sink::Sink<__Item2, Error = T>,
+    {}
+    #[allow(unsafe_code)]
+    impl<'__a, __Item, __Item1, const N: usize, __Item2> ::futures::sink::Sink<__Item2>
+    for Enum<'__a, __Item, __Item1, N>
+    where
+        &'__a mut [__Item; N]: ::futures::sink::Sink<__Item2>,
+        __Item1: ::futures::sink::Sink<__Item2>,
+        __Item1: __AssertError1<
+            __Item2,
+            <&'__a mut [__Item; N] as ::futures::sink::Sink<__Item2>>::Error,
+            <__Item1 as ::futures::sink::Sink<__Item2>>::Error,
+        >,
+    {
+        type Error = <&'__a mut [__Item; N] as ::futures::sink::Sink<__Item2>>::Error;
+        #[inline]
+        fn poll_ready(
+            self: ::core::pin::Pin<&mut Self>,
+            cx: &mut ::core::task::Context<'_>,
+        ) -> ::core::task::Poll<::core::result::Result<(), Self::Error>> {
+            unsafe {
+                match self.get_unchecked_mut() {
+                    Enum::A(x) => {
+                        ::futures::sink::Sink::poll_ready(
+                            ::core::pin::Pin::new_unchecked(x),
+                            cx,
+                        )
+                    }
+                    Enum::B(x) => {
+                        ::futures::sink::Sink::poll_ready(
+                            ::core::pin::Pin::new_unchecked(x),
+                            cx,
+                        )
+                    }
+                }
+            }
+        }
+        #[inline]
+        fn start_send(
+            self: ::core::pin::Pin<&mut Self>,
+            item: __Item2,
+        ) -> ::core::result::Result<(), Self::Error> {
+            unsafe {
+                match self.get_unchecked_mut() {
+                    Enum::A(x) => {
+                        ::futures::sink::Sink::start_send(
+                            ::core::pin::Pin::new_unchecked(x),
+                            item,
+                        )
+                    }
+                    Enum::B(x) => {
+                        ::futures::sink::Sink::start_send(
+                            ::core::pin::Pin::new_unchecked(x),
+                            item,
+                        )
+                    }
+                }
+            }
+        }
+        #[inline]
+        fn poll_flush(
+            self: ::core::pin::Pin<&mut Self>,
+            cx: &mut ::core::task::Context<'_>,
+        ) -> ::core::task::Poll<::core::result::Result<(), Self::Error>> {
+            unsafe {
+                match self.get_unchecked_mut() {
+                    Enum::A(x) => {
+                        ::futures::sink::Sink::poll_flush(
+                            ::core::pin::Pin::new_unchecked(x),
+                            cx,
+                        )
+                    }
+                    Enum::B(x) => {
+                        ::futures::sink::Sink::poll_flush(
+                            ::core::pin::Pin::new_unchecked(x),
+                            cx,
+                        )
+                    }
+                }
+            }
+        }
+        #[inline]
+        fn poll_close(
+            self: ::core::pin::Pin<&mut Self>,
+            cx: &mut ::core::task::Context<'_>,
+        ) -> ::core::task::Poll<::core::result::Result<(), Self::Error>> {
+            unsafe {
+                match self.get_unchecked_mut() {
+                    Enum::A(x) => {
+                        ::futures::sink::Sink::poll_close(
+                            ::core::pin::Pin::new_unchecked(x),
+                            cx,
+                        )
+                    }
+                    Enum::B(x) => {
+                        ::futures::sink::Sink::poll_close(
+                            ::core::pin::Pin::new_unchecked(x),
+                            cx,
+                        )
+                    }
+                }
+            }
+        }
+    }
+};
+#[allow(unsafe_code)]
+impl<'__a, __Item, __Item1, const N: usize> ::futures::io::AsyncBufRead
+for Enum<'__a, __Item, __Item1, N>
+where
+    &'__a mut [__Item; N]: ::futures::io::AsyncBufRead,
+    __Item1: ::futures::io::AsyncBufRead,
+{
+    #[inline]
+    fn poll_fill_buf<'__a1>(
+        self: ::core::pin::Pin<&'__a1 mut Self>,
+        cx: &mut ::core::task::Context<'_>,
+    ) -> ::core::task::Poll<::std::io::Result<&'__a1 [u8]>> {
+        unsafe {
+            match self.get_unchecked_mut() {
+                Enum::A(x) => {
+                    ::futures::io::AsyncBufRead::poll_fill_buf(
+                        ::core::pin::Pin::new_unchecked(x),
+                        cx,
+                    )
+                }
+                Enum::B(x) => {
+                    ::futures::io::AsyncBufRead::poll_fill_buf(
+                        ::core::pin::Pin::new_unchecked(x),
+                        cx,
+                    )
+                }
+            }
+        }
+    }
+    #[inline]
+    fn consume(self: ::core::pin::Pin<&mut Self>, amt: usize) {
+        unsafe {
+            match self.get_unchecked_mut() {
+                Enum::A(x) => {
+                    ::futures::io::AsyncBufRead::consume(
+                        ::core::pin::Pin::new_unchecked(x),
+                        amt,
+                    )
+                }
+                Enum::B(x) => {
+                    ::futures::io::AsyncBufRead::consume(
+                        ::core::pin::Pin::new_unchecked(x),
+                        amt,
+                    )
+                }
+            }
+        }
+    }
+}
+fn main() {}
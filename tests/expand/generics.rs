@@ -0,0 +1,12 @@
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use futures_enum::*;
+
+// Generic parameters with the same names as the ones used in the expansion.
+#[derive(Stream, Sink, AsyncBufRead)]
+enum Enum<'__a, __Item, __Item1, const N: usize> {
+    A(&'__a mut [__Item; N]),
+    B(__Item1),
+}
+
+fn main() {}
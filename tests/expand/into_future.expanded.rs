@@ -0,0 +1,83 @@
+use futures_enum::*;
+enum Enum<A, B> {
+    A(A),
+    B(B),
+}
+/// The `IntoFuture::IntoFuture` type of [`Enum`].
+enum EnumIntoFuture<A, B>
+where
+    A: ::core::future::IntoFuture,
+    B: ::core::future::IntoFuture,
+{
+    A(<A as ::core::future::IntoFuture>::IntoFuture),
+    B(<B as ::core::future::IntoFuture>::IntoFuture),
+}
+const _: () = {
+    #[diagnostic::on_unimplemented(
+        message = "variant `B` has `Output = {U}`, but variant `A` has `Output = {T}`",
+        label = "expected `Output = {T}`",
+        note = "all variants must have the same `Output`"
+    )]
+    trait __AssertOutput1<
+        T: ?::core::marker::Sized,
+        U: ?::core::marker::Sized,
+    >: ::core::future::Future<Output = T> {}
+    #[diagnostic::do_not_recommend]
+    impl<F, T: ?::core::marker::Sized> __AssertOutput1<T, T> for F
+    where
+        F: ?::core::marker::Sized + ::core::future::Future<Output = T>,
+    {}
+    #[allow(unsafe_code)]
+    impl<A, B> ::core::future::Future for EnumIntoFuture<A, B>
+    where
+        A: ::core::future::IntoFuture,
+        B: ::core::future::IntoFuture,
+        <A as ::core::future::IntoFuture>::IntoFuture: ::core::future::Future,
+        <B as ::core::future::IntoFuture>::IntoFuture: ::core::future::Future,
+        <B as ::core::future::IntoFuture>::IntoFuture: __AssertOutput1<
+            <<A as ::core::future::IntoFuture>::IntoFuture as ::core::future::Future>::Output,
+            <<B as ::core::future::IntoFuture>::IntoFuture as ::core::future::Future>::Output,
+        >,
+    {
+        type Output = <<A as ::core::future::IntoFuture>::IntoFuture as ::core::future::Future>::Output;
+        #[inline]
+        fn poll(
+            self: ::core::pin::Pin<&mut Self>,
+            cx: &mut ::core::task::Context<'_>,
+        ) -> ::core::task::Poll<Self::Output> {
+            unsafe {
+                match self.get_unchecked_mut() {
+                    EnumIntoFuture::A(x) => {
+                        ::core::future::Future::poll(
+                            ::core::pin::Pin::new_unchecked(x),
+                            cx,
+                        )
+                    }
+                    EnumIntoFuture::B(x) => {
+                        ::core::future::Future::poll(
+                            ::core::pin::Pin::new_unchecked(x),
+                            cx,
+                        )
+                    }
+                }
+            }
+        }
+    }
+};
+impl<A, B> ::core::future::IntoFuture for Enum<A, B>
+where
+    A: ::core::future::IntoFuture,
+    B: ::core::future::IntoFuture,
+    EnumIntoFuture<A, B>: ::core::future::Future,
+{
+    type Output = <EnumIntoFuture<A, B> as ::core::future::Future>::Output;
+    type IntoFuture = EnumIntoFuture<A, B>;
+    #[inline]
+    fn into_future(self) -> Self::IntoFuture {
+        match self {
+            Enum::A(x) => EnumIntoFuture::A(::core::future::IntoFuture::into_future(x)),
+            Enum::B(x) => EnumIntoFuture::B(::core::future::IntoFuture::into_future(x)),
+        }
+    }
+}
+fn main() {}
@@ -0,0 +1,198 @@
+use futures_enum::*;
+#[futures_enum(join)]
+struct Join<A, B> {
+    a: A,
+    b: B,
+}
+/// The outputs of the fields of [`Join`].
+struct JoinOutput<A, B>
+where
+    A: ::core::future::Future,
+    B: ::core::future::Future,
+{
+    a: <A as ::core::future::Future>::Output,
+    b: <B as ::core::future::Future>::Output,
+}
+/// The future that [`Join`] is converted into by `IntoFuture`, which completes when all the fields have completed.
+struct JoinJoin<A, B>
+where
+    A: ::core::future::Future,
+    B: ::core::future::Future,
+{
+    a: JoinJoinState<A>,
+    b: JoinJoinState<B>,
+}
+enum JoinJoinState<F: ::core::future::Future> {
+    Future(F),
+    Done(F::Output),
+    Gone,
+}
+impl<A, B> ::core::future::IntoFuture for Join<A, B>
+where
+    A: ::core::future::Future,
+    B: ::core::future::Future,
+{
+    type Output = JoinOutput<A, B>;
+    type IntoFuture = JoinJoin<A, B>;
+    #[inline]
+    fn into_future(self) -> Self::IntoFuture {
+        JoinJoin {
+            a: JoinJoinState::Future(self.a),
+            b: JoinJoinState::Future(self.b),
+        }
+    }
+}
+#[allow(unsafe_code)]
+impl<A, B> ::core::future::Future for JoinJoin<A, B>
+where
+    A: ::core::future::Future,
+    B: ::core::future::Future,
+{
+    type Output = JoinOutput<A, B>;
+    fn poll(
+        self: ::core::pin::Pin<&mut Self>,
+        cx: &mut ::core::task::Context<'_>,
+    ) -> ::core::task::Poll<Self::Output> {
+        let this = unsafe { self.get_unchecked_mut() };
+        let mut done = true;
+        if let JoinJoinState::Future(x) = &mut this.a {
+            match <A as ::core::future::Future>::poll(
+                unsafe { ::core::pin::Pin::new_unchecked(x) },
+                cx,
+            ) {
+                ::core::task::Poll::Ready(x) => this.a = JoinJoinState::Done(x),
+                ::core::task::Poll::Pending => done = false,
+            }
+        }
+        if let JoinJoinState::Future(x) = &mut this.b {
+            match <B as ::core::future::Future>::poll(
+                unsafe { ::core::pin::Pin::new_unchecked(x) },
+                cx,
+            ) {
+                ::core::task::Poll::Ready(x) => this.b = JoinJoinState::Done(x),
+                ::core::task::Poll::Pending => done = false,
+            }
+        }
+        if !done {
+            return ::core::task::Poll::Pending;
+        }
+        ::core::task::Poll::Ready(JoinOutput {
+            a: match ::core::mem::replace(&mut this.a, JoinJoinState::Gone) {
+                JoinJoinState::Done(x) => x,
+                _ => {
+                    ::core::panicking::panic_fmt(
+                        format_args!("`JoinJoin` polled after completion"),
+                    );
+                }
+            },
+            b: match ::core::mem::replace(&mut this.b, JoinJoinState::Gone) {
+                JoinJoinState::Done(x) => x,
+                _ => {
+                    ::core::panicking::panic_fmt(
+                        format_args!("`JoinJoin` polled after completion"),
+                    );
+                }
+            },
+        })
+    }
+}
+#[futures_enum(join)]
+struct Const<A, const N: usize> {
+    a: A,
+    b: std::future::Ready<[u8; N]>,
+}
+/// The outputs of the fields of [`Const`].
+struct ConstOutput<A, const N: usize>
+where
+    A: ::core::future::Future,
+    std::future::Ready<[u8; N]>: ::core::future::Future,
+{
+    a: <A as ::core::future::Future>::Output,
+    b: <std::future::Ready<[u8; N]> as ::core::future::Future>::Output,
+}
+/// The future that [`Const`] is converted into by `IntoFuture`, which completes when all the fields have completed.
+struct ConstJoin<A, const N: usize>
+where
+    A: ::core::future::Future,
+    std::future::Ready<[u8; N]>: ::core::future::Future,
+{
+    a: ConstJoinState<A>,
+    b: ConstJoinState<std::future::Ready<[u8; N]>>,
+}
+enum ConstJoinState<F: ::core::future::Future> {
+    Future(F),
+    Done(F::Output),
+    Gone,
+}
+impl<A, const N: usize> ::core::future::IntoFuture for Const<A, N>
+where
+    A: ::core::future::Future,
+    std::future::Ready<[u8; N]>: ::core::future::Future,
+{
+    type Output = ConstOutput<A, N>;
+    type IntoFuture = ConstJoin<A, N>;
+    #[inline]
+    fn into_future(self) -> Self::IntoFuture {
+        ConstJoin {
+            a: ConstJoinState::Future(self.a),
+            b: ConstJoinState::Future(self.b),
+        }
+    }
+}
+#[allow(unsafe_code)]
+impl<A, const N: usize> ::core::future::Future for ConstJoin<A, N>
+where
+    A: ::core::future::Future,
+    std::future::Ready<[u8; N]>: ::core::future::Future,
+{
+    type Output = ConstOutput<A, N>;
+    fn poll(
+        self: ::core::pin::Pin<&mut Self>,
+        cx: &mut ::core::task::Context<'_>,
+    ) -> ::core::task::Poll<Self::Output> {
+        let this = unsafe { self.get_unchecked_mut() };
+        let mut done = true;
+        if let ConstJoinState::Future(x) = &mut this.a {
+            match <A as ::core::future::Future>::poll(
+                unsafe { ::core::pin::Pin::new_unchecked(x) },
+                cx,
+            ) {
+                ::core::task::Poll::Ready(x) => this.a = ConstJoinState::Done(x),
+                ::core::task::Poll::Pending => done = false,
+            }
+        }
+        if let ConstJoinState::Future(x) = &mut this.b {
+            match <std::future::Ready<
+                [u8; N],
+            > as ::core::future::Future>::poll(
+                unsafe { ::core::pin::Pin::new_unchecked(x) },
+                cx,
+            ) {
+                ::core::task::Poll::Ready(x) => this.b = ConstJoinState::Done(x),
+                ::core::task::Poll::Pending => done = false,
+            }
+        }
+        if !done {
+            return ::core::task::Poll::Pending;
+        }
+        ::core::task::Poll::Ready(ConstOutput {
+            a: match ::core::mem::replace(&mut this.a, ConstJoinState::Gone) {
+                ConstJoinState::Done(x) => x,
+                _ => {
+                    ::core::panicking::panic_fmt(
+                        format_args!("`ConstJoin` polled after completion"),
+                    );
+                }
+            },
+            b: match ::core::mem::replace(&mut this.b, ConstJoinState::Gone) {
+                ConstJoinState::Done(x) => x,
+                _ => {
+                    ::core::panicking::panic_fmt(
+                        format_args!("`ConstJoin` polled after completion"),
+                    );
+                }
+            },
+        })
+    }
+}
+fn main() {}
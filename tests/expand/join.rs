@@ -0,0 +1,19 @@
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use futures_enum::*;
+
+#[derive(IntoFuture)]
+#[futures_enum(join)]
+struct Join<A, B> {
+    a: A,
+    b: B,
+}
+
+#[derive(IntoFuture)]
+#[futures_enum(join)]
+struct Const<A, const N: usize> {
+    a: A,
+    b: std::future::Ready<[u8; N]>,
+}
+
+fn main() {}
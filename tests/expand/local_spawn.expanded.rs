@@ -0,0 +1,29 @@
+use futures_enum::*;
+enum Enum<A, B> {
+    A(A),
+    B(B),
+}
+impl<A, B> ::futures::task::LocalSpawn for Enum<A, B>
+where
+    A: ::futures::task::LocalSpawn,
+    B: ::futures::task::LocalSpawn,
+{
+    #[inline]
+    fn spawn_local_obj(
+        &self,
+        future: ::futures::task::LocalFutureObj<'static, ()>,
+    ) -> ::core::result::Result<(), ::futures::task::SpawnError> {
+        match self {
+            Enum::A(x) => ::futures::task::LocalSpawn::spawn_local_obj(x, future),
+            Enum::B(x) => ::futures::task::LocalSpawn::spawn_local_obj(x, future),
+        }
+    }
+    #[inline]
+    fn status_local(&self) -> ::core::result::Result<(), ::futures::task::SpawnError> {
+        match self {
+            Enum::A(x) => ::futures::task::LocalSpawn::status_local(x),
+            Enum::B(x) => ::futures::task::LocalSpawn::status_local(x),
+        }
+    }
+}
+fn main() {}
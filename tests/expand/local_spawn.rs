@@ -0,0 +1,11 @@
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use futures_enum::*;
+
+#[derive(LocalSpawn)]
+enum Enum<A, B> {
+    A(A),
+    B(B),
+}
+
+fn main() {}
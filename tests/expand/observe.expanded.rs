@@ -0,0 +1,165 @@
+use std::time::Duration;
+use futures_enum::*;
+struct Observer;
+impl Observer {
+    fn on_poll(_: &'static str, _: &'static str, _: &'static str, _: Duration, _: bool) {}
+    fn on_bytes(_: &'static str, _: &'static str, _: &'static str, _: usize) {}
+}
+#[futures_enum(observe = "Observer")]
+enum Enum<A, B> {
+    A(A),
+    B(B),
+}
+const _: () = {
+    #[diagnostic::on_unimplemented(
+        message = "variant `B` has `Output = {U}`, but variant `A` has `Output = {T}`",
+        label = "expected `Output = {T}`",
+        note = "all variants must have the same `Output`"
+    )]
+    trait __AssertOutput1<
+        T: ?::core::marker::Sized,
+        U: ?::core::marker::Sized,
+    >: ::core::future::Future<Output = T> {}
+    #[diagnostic::do_not_recommend]
+    impl<F, T: ?::core::marker::Sized> __AssertOutput1<T, T> for F
+    where
+        F: ?::core::marker::Sized + ::core::future::Future<Output = T>,
+    {}
+    #[allow(unsafe_code)]
+    impl<A, B> ::core::future::Future for Enum<A, B>
+    where
+        A: ::core::future::Future,
+        B: ::core::future::Future,
+        B: __AssertOutput1<
+            <A as ::core::future::Future>::Output,
+            <B as ::core::future::Future>::Output,
+        >,
+    {
+        type Output = <A as ::core::future::Future>::Output;
+        #[inline]
+        fn poll(
+            self: ::core::pin::Pin<&mut Self>,
+            cx: &mut ::core::task::Context<'_>,
+        ) -> ::core::task::Poll<Self::Output> {
+            let variant = match &*self {
+                Enum::A(_) => "A",
+                Enum::B(_) => "B",
+            };
+            let start = ::std::time::Instant::now();
+            let poll = {
+                unsafe {
+                    match self.get_unchecked_mut() {
+                        Enum::A(x) => {
+                            ::core::future::Future::poll(
+                                ::core::pin::Pin::new_unchecked(x),
+                                cx,
+                            )
+                        }
+                        Enum::B(x) => {
+                            ::core::future::Future::poll(
+                                ::core::pin::Pin::new_unchecked(x),
+                                cx,
+                            )
+                        }
+                    }
+                }
+            };
+            Observer::on_poll("Enum", variant, "poll", start.elapsed(), poll.is_ready());
+            poll
+        }
+    }
+};
+#[allow(unsafe_code)]
+impl<A, B> ::futures::io::AsyncRead for Enum<A, B>
+where
+    A: ::futures::io::AsyncRead,
+    B: ::futures::io::AsyncRead,
+{
+    #[inline]
+    fn poll_read(
+        self: ::core::pin::Pin<&mut Self>,
+        cx: &mut ::core::task::Context<'_>,
+        buf: &mut [u8],
+    ) -> ::core::task::Poll<::std::io::Result<usize>> {
+        let variant = match &*self {
+            Enum::A(_) => "A",
+            Enum::B(_) => "B",
+        };
+        let start = ::std::time::Instant::now();
+        let poll = {
+            unsafe {
+                match self.get_unchecked_mut() {
+                    Enum::A(x) => {
+                        ::futures::io::AsyncRead::poll_read(
+                            ::core::pin::Pin::new_unchecked(x),
+                            cx,
+                            buf,
+                        )
+                    }
+                    Enum::B(x) => {
+                        ::futures::io::AsyncRead::poll_read(
+                            ::core::pin::Pin::new_unchecked(x),
+                            cx,
+                            buf,
+                        )
+                    }
+                }
+            }
+        };
+        Observer::on_poll(
+            "Enum",
+            variant,
+            "poll_read",
+            start.elapsed(),
+            poll.is_ready(),
+        );
+        if let ::core::task::Poll::Ready(::core::result::Result::Ok(bytes)) = &poll {
+            Observer::on_bytes("Enum", variant, "poll_read", *bytes);
+        }
+        poll
+    }
+    #[inline]
+    fn poll_read_vectored(
+        self: ::core::pin::Pin<&mut Self>,
+        cx: &mut ::core::task::Context<'_>,
+        bufs: &mut [::std::io::IoSliceMut<'_>],
+    ) -> ::core::task::Poll<::std::io::Result<usize>> {
+        let variant = match &*self {
+            Enum::A(_) => "A",
+            Enum::B(_) => "B",
+        };
+        let start = ::std::time::Instant::now();
+        let poll = {
+            unsafe {
+                match self.get_unchecked_mut() {
+                    Enum::A(x) => {
+                        ::futures::io::AsyncRead::poll_read_vectored(
+                            ::core::pin::Pin::new_unchecked(x),
+                            cx,
+                            bufs,
+                        )
+                    }
+                    Enum::B(x) => {
+                        ::futures::io::AsyncRead::poll_read_vectored(
+                            ::core::pin::Pin::new_unchecked(x),
+                            cx,
+                            bufs,
+                        )
+                    }
+                }
+            }
+        };
+        Observer::on_poll(
+            "Enum",
+            variant,
+            "poll_read_vectored",
+            start.elapsed(),
+            poll.is_ready(),
+        );
+        if let ::core::task::Poll::Ready(::core::result::Result::Ok(bytes)) = &poll {
+            Observer::on_bytes("Enum", variant, "poll_read_vectored", *bytes);
+        }
+        poll
+    }
+}
+fn main() {}
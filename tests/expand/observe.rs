@@ -0,0 +1,21 @@
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use std::time::Duration;
+
+use futures_enum::*;
+
+struct Observer;
+
+impl Observer {
+    fn on_poll(_: &'static str, _: &'static str, _: &'static str, _: Duration, _: bool) {}
+    fn on_bytes(_: &'static str, _: &'static str, _: &'static str, _: usize) {}
+}
+
+#[derive(Future, AsyncRead)]
+#[futures_enum(observe = "Observer")]
+enum Enum<A, B> {
+    A(A),
+    B(B),
+}
+
+fn main() {}
@@ -0,0 +1,87 @@
+use futures_enum::*;
+#[futures_enum(on_end = "Self::next")]
+enum Enum<A, B> {
+    A(A),
+    B(B),
+}
+const _: () = {
+    #[diagnostic::on_unimplemented(
+        message = "variant `B` has `Item = {U}`, but variant `A` has `Item = {T}`",
+        label = "expected `Item = {T}`",
+        note = "all variants must have the same `Item`"
+    )]
+    trait __AssertItem1<
+        T: ?::core::marker::Sized,
+        U: ?::core::marker::Sized,
+    >: ::futures::stream::Stream<Item = T> {}
+    #[diagnostic::do_not_recommend]
+    impl<F, T: ?::core::marker::Sized> __AssertItem1<T, T> for F
+    where
+        F: ?::core::marker::Sized + ::futures::stream::Stream<Item = T>,
+    {}
+    #[allow(unsafe_code)]
+    impl<A, B> ::futures::stream::Stream for Enum<A, B>
+    where
+        A: ::futures::stream::Stream,
+        B: ::futures::stream::Stream,
+        B: __AssertItem1<
+            <A as ::futures::stream::Stream>::Item,
+            <B as ::futures::stream::Stream>::Item,
+        >,
+    {
+        type Item = <A as ::futures::stream::Stream>::Item;
+        #[inline]
+        fn poll_next(
+            mut self: ::core::pin::Pin<&mut Self>,
+            cx: &mut ::core::task::Context<'_>,
+        ) -> ::core::task::Poll<::core::option::Option<Self::Item>> {
+            loop {
+                let poll = unsafe {
+                    match self.as_mut().get_unchecked_mut() {
+                        Enum::A(x) => {
+                            <A as ::futures::stream::Stream>::poll_next(
+                                ::core::pin::Pin::new_unchecked(x),
+                                cx,
+                            )
+                        }
+                        Enum::B(x) => {
+                            <B as ::futures::stream::Stream>::poll_next(
+                                ::core::pin::Pin::new_unchecked(x),
+                                cx,
+                            )
+                        }
+                    }
+                };
+                match poll {
+                    ::core::task::Poll::Ready(::core::option::Option::None) => {
+                        match Self::next(&*self) {
+                            ::core::option::Option::Some(next) => self.set(next),
+                            ::core::option::Option::None => {
+                                break ::core::task::Poll::Ready(
+                                    ::core::option::Option::None,
+                                );
+                            }
+                        }
+                    }
+                    poll => break poll,
+                }
+            }
+        }
+        #[inline]
+        fn size_hint(&self) -> (usize, ::core::option::Option<usize>) {
+            let (lower, _) = {
+                match self {
+                    Enum::A(x) => ::futures::stream::Stream::size_hint(x),
+                    Enum::B(x) => ::futures::stream::Stream::size_hint(x),
+                }
+            };
+            (lower, ::core::option::Option::None)
+        }
+    }
+};
+impl<A, B> Enum<A, B> {
+    fn next(&self) -> Option<Self> {
+        None
+    }
+}
+fn main() {}
@@ -0,0 +1,18 @@
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use futures_enum::*;
+
+#[derive(Stream)]
+#[futures_enum(on_end = "Self::next")]
+enum Enum<A, B> {
+    A(A),
+    B(B),
+}
+
+impl<A, B> Enum<A, B> {
+    fn next(&self) -> Option<Self> {
+        None
+    }
+}
+
+fn main() {}
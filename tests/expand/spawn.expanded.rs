@@ -0,0 +1,29 @@
+use futures_enum::*;
+enum Enum<A, B> {
+    A(A),
+    B(B),
+}
+impl<A, B> ::futures::task::Spawn for Enum<A, B>
+where
+    A: ::futures::task::Spawn,
+    B: ::futures::task::Spawn,
+{
+    #[inline]
+    fn spawn_obj(
+        &self,
+        future: ::futures::task::FutureObj<'static, ()>,
+    ) -> ::core::result::Result<(), ::futures::task::SpawnError> {
+        match self {
+            Enum::A(x) => ::futures::task::Spawn::spawn_obj(x, future),
+            Enum::B(x) => ::futures::task::Spawn::spawn_obj(x, future),
+        }
+    }
+    #[inline]
+    fn status(&self) -> ::core::result::Result<(), ::futures::task::SpawnError> {
+        match self {
+            Enum::A(x) => ::futures::task::Spawn::status(x),
+            Enum::B(x) => ::futures::task::Spawn::status(x),
+        }
+    }
+}
+fn main() {}
@@ -0,0 +1,79 @@
+use std::{future::Ready, ops::ControlFlow};
+use futures_enum::*;
+#[futures_enum(then = "Self::advance", output = "String")]
+enum Enum {
+    A(Ready<u8>),
+    B(Ready<String>),
+}
+/// The outputs of the variants of [`Enum`].
+enum EnumOutput
+where
+    Ready<u8>: ::core::future::Future,
+    Ready<String>: ::core::future::Future,
+{
+    A(<Ready<u8> as ::core::future::Future>::Output),
+    B(<Ready<String> as ::core::future::Future>::Output),
+}
+#[allow(unsafe_code)]
+impl ::core::future::Future for Enum
+where
+    Ready<u8>: ::core::future::Future,
+    Ready<String>: ::core::future::Future,
+{
+    type Output = String;
+    #[inline]
+    fn poll(
+        mut self: ::core::pin::Pin<&mut Self>,
+        cx: &mut ::core::task::Context<'_>,
+    ) -> ::core::task::Poll<Self::Output> {
+        loop {
+            let output = unsafe {
+                match self.as_mut().get_unchecked_mut() {
+                    Enum::A(x) => {
+                        match <Ready<
+                            u8,
+                        > as ::core::future::Future>::poll(
+                            ::core::pin::Pin::new_unchecked(x),
+                            cx,
+                        ) {
+                            ::core::task::Poll::Ready(x) => EnumOutput::A(x),
+                            ::core::task::Poll::Pending => {
+                                break ::core::task::Poll::Pending;
+                            }
+                        }
+                    }
+                    Enum::B(x) => {
+                        match <Ready<
+                            String,
+                        > as ::core::future::Future>::poll(
+                            ::core::pin::Pin::new_unchecked(x),
+                            cx,
+                        ) {
+                            ::core::task::Poll::Ready(x) => EnumOutput::B(x),
+                            ::core::task::Poll::Pending => {
+                                break ::core::task::Poll::Pending;
+                            }
+                        }
+                    }
+                }
+            };
+            match Self::advance(output) {
+                ::core::ops::ControlFlow::Break(output) => {
+                    break ::core::task::Poll::Ready(output);
+                }
+                ::core::ops::ControlFlow::Continue(next) => self.set(next),
+            }
+        }
+    }
+}
+impl Enum {
+    fn advance(output: EnumOutput) -> ControlFlow<String, Self> {
+        match output {
+            EnumOutput::A(x) => {
+                ControlFlow::Continue(Self::B(std::future::ready(x.to_string())))
+            }
+            EnumOutput::B(x) => ControlFlow::Break(x),
+        }
+    }
+}
+fn main() {}
@@ -0,0 +1,23 @@
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use std::{future::Ready, ops::ControlFlow};
+
+use futures_enum::*;
+
+#[derive(Future)]
+#[futures_enum(then = "Self::advance", output = "String")]
+enum Enum {
+    A(Ready<u8>),
+    B(Ready<String>),
+}
+
+impl Enum {
+    fn advance(output: EnumOutput) -> ControlFlow<String, Self> {
+        match output {
+            EnumOutput::A(x) => ControlFlow::Continue(Self::B(std::future::ready(x.to_string()))),
+            EnumOutput::B(x) => ControlFlow::Break(x),
+        }
+    }
+}
+
+fn main() {}
@@ -0,0 +1,84 @@
+use futures_enum::*;
+pub enum Enum<A, B> {
+    A(A),
+    B(B),
+}
+const _: () = {
+    #[diagnostic::on_unimplemented(
+        message = "variant `B` has `Output = {U}`, but variant `A` has `Output = {T}`",
+        label = "expected `Output = {T}`",
+        note = "all variants must have the same `Output`"
+    )]
+    trait __AssertOutput1<
+        T: ?::core::marker::Sized,
+        U: ?::core::marker::Sized,
+    >: ::core::future::Future<Output = T> {}
+    #[diagnostic::do_not_recommend]
+    impl<F, T: ?::core::marker::Sized> __AssertOutput1<T, T> for F
+    where
+        F: ?::core::marker::Sized + ::core::future::Future<Output = T>,
+    {}
+    #[allow(unsafe_code)]
+    impl<A, B> ::core::future::Future for Enum<A, B>
+    where
+        A: ::core::future::Future,
+        B: ::core::future::Future,
+        B: __AssertOutput1<
+            <A as ::core::future::Future>::Output,
+            <B as ::core::future::Future>::Output,
+        >,
+    {
+        type Output = <A as ::core::future::Future>::Output;
+        #[inline]
+        fn poll(
+            self: ::core::pin::Pin<&mut Self>,
+            cx: &mut ::core::task::Context<'_>,
+        ) -> ::core::task::Poll<Self::Output> {
+            unsafe {
+                match self.get_unchecked_mut() {
+                    Enum::A(x) => {
+                        ::core::future::Future::poll(
+                            ::core::pin::Pin::new_unchecked(x),
+                            cx,
+                        )
+                    }
+                    Enum::B(x) => {
+                        ::core::future::Future::poll(
+                            ::core::pin::Pin::new_unchecked(x),
+                            cx,
+                        )
+                    }
+                }
+            }
+        }
+    }
+};
+impl<A, B> Enum<A, B> {
+    /// Returns the name of the active variant.
+    #[inline]
+    pub fn variant_name(&self) -> &'static str {
+        match self {
+            Enum::A(_) => "A",
+            Enum::B(_) => "B",
+        }
+    }
+    /// Returns the index of the active variant, in declaration order.
+    #[inline]
+    pub fn variant_index(&self) -> usize {
+        match self {
+            Enum::A(_) => 0usize,
+            Enum::B(_) => 1usize,
+        }
+    }
+}
+impl<A, B> ::core::fmt::Debug for Enum<A, B> {
+    fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
+        f.write_str(
+            match self {
+                Enum::A(_) => "Enum::A(..)",
+                Enum::B(_) => "Enum::B(..)",
+            },
+        )
+    }
+}
+fn main() {}
@@ -0,0 +1,11 @@
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use futures_enum::*;
+
+#[derive(Future, VariantName, OpaqueDebug)]
+pub enum Enum<A, B> {
+    A(A),
+    B(B),
+}
+
+fn main() {}
@@ -4,21 +4,789 @@
 
 use std::future::Future;
 
-use futures::{AsyncBufRead, AsyncRead, AsyncSeek, AsyncWrite, Sink, Stream};
-use futures_enum::{AsyncBufRead, AsyncRead, AsyncSeek, AsyncWrite, Future, Sink, Stream};
+use futures::{
+    AsyncBufRead, AsyncRead, AsyncSeek, AsyncWrite, Sink, Stream,
+    task::{LocalSpawn, Spawn},
+};
+use futures_enum::{
+    AsyncBufRead, AsyncRead, AsyncSeek, AsyncWrite, Future, LocalSpawn, Sink, Spawn, Stream,
+};
 
-#[derive(Future, Stream, Sink, AsyncRead, AsyncWrite, AsyncSeek, AsyncBufRead)]
+#[derive(
+    Future, Stream, Sink, AsyncRead, AsyncWrite, AsyncSeek, AsyncBufRead, Spawn, LocalSpawn,
+)]
 enum Either<A, B> {
     A(A),
     B(B),
 }
 
 fn _assert_impl<
-    T: Future + Stream + Sink<()> + AsyncRead + AsyncWrite + AsyncSeek + AsyncBufRead,
+    T: Future
+        + Stream
+        + Sink<()>
+        + AsyncRead
+        + AsyncWrite
+        + AsyncSeek
+        + AsyncBufRead
+        + Spawn
+        + LocalSpawn,
 >() {
     fn __assert_impl<
-        T: Future + Stream + Sink<()> + AsyncRead + AsyncWrite + AsyncSeek + AsyncBufRead,
+        T: Future
+            + Stream
+            + Sink<()>
+            + AsyncRead
+            + AsyncWrite
+            + AsyncSeek
+            + AsyncBufRead
+            + Spawn
+            + LocalSpawn,
     >() {
     }
     __assert_impl::<Either<T, T>>();
 }
+
+mod crate_path {
+    use futures::{AsyncBufRead, AsyncRead, Sink, Stream, task::Spawn};
+    use futures_enum::{AsyncBufRead, AsyncRead, Sink, Spawn, Stream};
+
+    mod facade {
+        pub(crate) mod futures {
+            pub(crate) use ::futures::*;
+        }
+        pub(crate) mod io {
+            pub(crate) use futures::io::{AsyncBufRead, AsyncRead};
+        }
+    }
+
+    #[derive(Stream, Sink, AsyncRead, AsyncBufRead, Spawn)]
+    #[futures_enum(crate = "self::facade::futures", io = "self::facade::io")]
+    enum Either<A, B> {
+        A(A),
+        B(B),
+    }
+
+    #[derive(Sink)]
+    #[futures_enum(sink = "::futures::sink")]
+    enum Sinks<A, B> {
+        A(A),
+        B(B),
+    }
+
+    fn _assert_impl<T: Stream + Sink<()> + AsyncRead + AsyncBufRead + Spawn>() {
+        fn __assert_impl<T: Stream + Sink<()> + AsyncRead + AsyncBufRead + Spawn>() {}
+        fn __assert_sink<T: Sink<()>>() {}
+        __assert_impl::<Either<T, T>>();
+        __assert_sink::<Sinks<T, T>>();
+    }
+}
+
+mod into_future {
+    use std::future::{self, IntoFuture, Ready};
+
+    use futures_enum::IntoFuture;
+
+    struct Request(u8);
+
+    impl IntoFuture for Request {
+        type Output = u8;
+        type IntoFuture = Ready<u8>;
+        fn into_future(self) -> Self::IntoFuture {
+            future::ready(self.0)
+        }
+    }
+
+    #[derive(IntoFuture)]
+    enum Either<A, B> {
+        A(A),
+        B(B),
+    }
+
+    async fn _await(x: Either<Request, Ready<u8>>) -> u8 {
+        x.await
+    }
+}
+
+mod delegate_async_trait {
+    use std::{future::Future, pin::Pin};
+
+    use async_trait::async_trait;
+    use futures_enum::delegate_async_trait;
+
+    #[async_trait]
+    trait Storage {
+        async fn get(&self, key: &str) -> Vec<u8>;
+        async fn put(&mut self, key: String, value: Vec<u8>);
+        fn len<'a>(&'a self) -> Pin<Box<dyn Future<Output = usize> + Send + 'a>>;
+    }
+
+    struct Fs;
+    struct Memory(Vec<(String, Vec<u8>)>);
+
+    #[async_trait]
+    impl Storage for Fs {
+        async fn get(&self, _key: &str) -> Vec<u8> {
+            vec![]
+        }
+        async fn put(&mut self, _key: String, _value: Vec<u8>) {}
+        fn len<'a>(&'a self) -> Pin<Box<dyn Future<Output = usize> + Send + 'a>> {
+            Box::pin(async { 0 })
+        }
+    }
+
+    #[async_trait]
+    impl Storage for Memory {
+        async fn get(&self, key: &str) -> Vec<u8> {
+            self.0.iter().find(|(k, _)| k == key).map(|(_, v)| v.clone()).unwrap_or_default()
+        }
+        async fn put(&mut self, key: String, value: Vec<u8>) {
+            self.0.push((key, value));
+        }
+        fn len<'a>(&'a self) -> Pin<Box<dyn Future<Output = usize> + Send + 'a>> {
+            Box::pin(async move { self.0.len() })
+        }
+    }
+
+    #[delegate_async_trait(Storage {
+        async fn get(&self, key: &str) -> Vec<u8>;
+        async fn put(&mut self, key: String, value: Vec<u8>);
+        fn len<'a>(&'a self) -> Pin<Box<dyn Future<Output = usize> + Send + 'a>>;
+    })]
+    enum Backend {
+        Fs(Fs),
+        Memory(Memory),
+    }
+
+    fn _assert_send(backend: &mut Backend) {
+        fn assert_send<T: Send>(_: T) {}
+        assert_send(backend.get(""));
+        assert_send(backend.len());
+        assert_send(backend.put(String::new(), vec![]));
+    }
+}
+
+mod dispatch {
+    use std::{error::Error, future::Future};
+
+    use futures::{
+        AsyncRead, AsyncReadExt as _, FutureExt as _, Sink, SinkExt as _, Stream, StreamExt as _,
+        io, sink, stream,
+    };
+    use futures_enum::dispatch;
+
+    #[dispatch]
+    fn future(x: i32) -> impl Future<Output = i32> {
+        if x < 0 {
+            return async { -1 };
+        }
+        match x {
+            0 => async { 0 },
+            1 => {
+                let y = x + 1;
+                async move { y }
+            }
+            _ => async move { x },
+        }
+    }
+
+    #[dispatch]
+    fn stream(x: bool) -> impl Stream<Item = u8> + Unpin {
+        if x { stream::iter(vec![1, 2]) } else { stream::empty() }
+    }
+
+    #[dispatch]
+    fn reader(x: bool) -> impl AsyncRead + Unpin {
+        if x { io::empty() } else { io::repeat(0) }
+    }
+
+    #[dispatch(into)]
+    fn future_into(x: bool) -> impl Future<Output = i64> {
+        if x { async { 1_i32 } } else { async { 2_u8 } }
+    }
+
+    #[dispatch(into)]
+    fn stream_into(x: bool) -> impl Stream<Item = String> + Unpin {
+        if x { stream::iter(vec!["a"]) } else { stream::iter(vec!['b']) }
+    }
+
+    #[dispatch(into)]
+    fn sink_into(x: bool) -> impl Sink<u8, Error = Box<dyn Error>> {
+        if x { sink::drain::<u8>().sink_map_err(io::Error::other) } else { sink::drain::<u8>() }
+    }
+
+    #[test]
+    fn test() {
+        assert_eq!(future(-5).now_or_never(), Some(-1));
+        assert_eq!(future(0).now_or_never(), Some(0));
+        assert_eq!(future(1).now_or_never(), Some(2));
+        assert_eq!(future(5).now_or_never(), Some(5));
+        assert_eq!(stream(true).collect::<Vec<_>>().now_or_never(), Some(vec![1, 2]));
+        assert_eq!(stream(false).collect::<Vec<_>>().now_or_never(), Some(vec![]));
+        let mut buf = [1; 4];
+        assert_eq!(reader(true).read(&mut buf).now_or_never().unwrap().unwrap(), 0);
+        assert_eq!(reader(false).read(&mut buf).now_or_never().unwrap().unwrap(), 4);
+        assert_eq!(buf, [0; 4]);
+        assert_eq!(future_into(true).now_or_never(), Some(1));
+        assert_eq!(future_into(false).now_or_never(), Some(2));
+        assert_eq!(stream_into(true).collect::<Vec<_>>().now_or_never(), Some(vec!["a".into()]));
+        assert_eq!(stream_into(false).collect::<Vec<_>>().now_or_never(), Some(vec!["b".into()]));
+        for x in [true, false] {
+            assert!(sink_into(x).send(1).now_or_never().unwrap().is_ok());
+        }
+    }
+}
+
+mod assert {
+    use std::{future::Future, pin::Pin};
+
+    use futures::{future::Ready, stream::BoxStream};
+    use futures_enum::{Future, Stream};
+
+    #[derive(Future)]
+    #[futures_enum(assert(Send, Sync, Unpin))]
+    enum Futures<'a, A> {
+        A(Ready<()>),
+        B(Pin<Box<dyn Future<Output = ()> + Send + Sync + 'a>>),
+        // Checked where the impl is used because it depends on the type parameter.
+        C(A),
+    }
+
+    #[derive(Stream)]
+    #[futures_enum(assert(Send))]
+    enum Streams<'a> {
+        A(BoxStream<'a, u8>),
+        B(futures::stream::Empty<u8>),
+    }
+
+    fn _assert_impl() {
+        fn __assert_impl<T: Future + Send + Sync + Unpin>() {}
+        fn __assert_stream<T: futures::Stream + Send>() {}
+        __assert_impl::<Futures<'_, Ready<()>>>();
+        __assert_stream::<Streams<'_>>();
+    }
+}
+
+mod generics {
+    use std::pin::Pin;
+
+    use futures::{AsyncBufRead, Sink, Stream, future};
+    use futures_enum::{AsyncBufRead, AsyncRead, Future, Sink, Stream};
+
+    // Generic parameters with the same names as the ones used in the expansion.
+    #[derive(Stream, Sink, AsyncRead, AsyncBufRead)]
+    enum Enum<'__a, __Item, __Item1> {
+        A(&'__a mut __Item),
+        B(__Item1),
+    }
+
+    // Variants of different types that do not refer to the generic parameters,
+    // whose associated types are checked in bounds with a lifetime of their own.
+    #[derive(Future)]
+    enum Concrete<'__a> {
+        A(future::Ready<u8>),
+        B(Pin<Box<dyn std::future::Future<Output = u8>>>),
+        C(&'__a mut future::Ready<u8>),
+    }
+
+    fn _assert_impl<'a, T: Stream + Sink<()> + AsyncBufRead + Unpin + 'a>() {
+        fn __assert_impl<T: Stream + Sink<()> + AsyncBufRead>() {}
+        fn __assert_future<T: std::future::Future<Output = u8>>() {}
+        __assert_impl::<Enum<'a, T, &'a mut T>>();
+        __assert_future::<Concrete<'a>>();
+    }
+}
+
+#[cfg(feature = "tracing")]
+mod trace {
+    use std::{
+        fmt,
+        sync::{Arc, Mutex},
+    };
+
+    use futures::{
+        AsyncRead, AsyncReadExt as _, AsyncWrite, FutureExt as _, Sink, Stream, StreamExt as _,
+        future, io, stream,
+    };
+    use futures_enum::{AsyncRead, AsyncWrite, Future, Sink, Stream};
+    use tracing::{
+        Event, Level, Metadata, Subscriber,
+        field::{Field, Visit},
+        span,
+    };
+
+    #[derive(Future, Stream, Sink, AsyncRead, AsyncWrite)]
+    #[futures_enum(trace)]
+    enum Enum<A, B> {
+        A(A),
+        B(B),
+    }
+
+    fn _assert_impl<T: std::future::Future + Stream + Sink<()> + AsyncRead + AsyncWrite>() {
+        fn __assert_impl<T: std::future::Future + Stream + Sink<()> + AsyncRead + AsyncWrite>() {}
+        __assert_impl::<Enum<T, T>>();
+    }
+
+    // Records the level and the fields of each event.
+    #[derive(Clone, Default)]
+    struct Recorder(Arc<Mutex<Vec<String>>>);
+
+    impl Recorder {
+        fn take(&self) -> Vec<String> {
+            std::mem::take(&mut self.0.lock().unwrap())
+        }
+    }
+
+    impl Subscriber for Recorder {
+        fn enabled(&self, _: &Metadata<'_>) -> bool {
+            true
+        }
+        fn new_span(&self, _: &span::Attributes<'_>) -> span::Id {
+            span::Id::from_u64(1)
+        }
+        fn record(&self, _: &span::Id, _: &span::Record<'_>) {}
+        fn record_follows_from(&self, _: &span::Id, _: &span::Id) {}
+        fn event(&self, event: &Event<'_>) {
+            struct Fields(Vec<String>);
+            impl Visit for Fields {
+                fn record_str(&mut self, field: &Field, value: &str) {
+                    self.0.push(format!("{}={value}", field.name()));
+                }
+                fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+                    self.0.push(format!("{}={value:?}", field.name()));
+                }
+            }
+            let mut fields = Fields(vec![event.metadata().level().to_string()]);
+            event.record(&mut fields);
+            self.0.lock().unwrap().push(fields.0.join(" "));
+        }
+        fn enter(&self, _: &span::Id) {}
+        fn exit(&self, _: &span::Id) {}
+    }
+
+    #[test]
+    fn test() {
+        let recorder = Recorder::default();
+        tracing::subscriber::with_default(recorder.clone(), || {
+            let f = Enum::<_, future::Ready<u8>>::A(future::pending::<u8>());
+            assert_eq!(f.now_or_never(), None);
+            let f = Enum::<future::Pending<u8>, _>::B(future::ready(1));
+            assert_eq!(f.now_or_never(), Some(1));
+
+            let mut s = Enum::<_, stream::Pending<u8>>::A(stream::iter([1]));
+            assert_eq!(s.next().now_or_never(), Some(Some(1)));
+
+            let mut buf = [1; 4];
+            let mut r = Enum::<io::Empty, _>::B(io::repeat(0));
+            assert_eq!(r.read(&mut buf).now_or_never().unwrap().unwrap(), 4);
+        });
+        assert_eq!(recorder.take(), [
+            format!("{} enum_name=Enum variant=A method=poll poll=Pending", Level::TRACE),
+            format!("{} enum_name=Enum variant=B method=poll poll=Ready", Level::TRACE),
+            format!("{} enum_name=Enum variant=A method=poll_next poll=Ready", Level::TRACE),
+            format!("{} enum_name=Enum variant=B method=poll_read poll=Ready", Level::TRACE),
+        ]);
+    }
+}
+
+mod observe {
+    use std::{cell::RefCell, time::Duration};
+
+    use futures::{AsyncReadExt as _, AsyncWriteExt as _, FutureExt as _, future, io};
+    use futures_enum::{AsyncRead, AsyncWrite, Future};
+
+    thread_local! {
+        static EVENTS: RefCell<Vec<String>> = const { RefCell::new(Vec::new()) };
+    }
+
+    struct Observer;
+
+    impl Observer {
+        fn on_poll(
+            enum_name: &'static str,
+            variant: &'static str,
+            method: &'static str,
+            _elapsed: Duration,
+            ready: bool,
+        ) {
+            EVENTS
+                .with(|e| e.borrow_mut().push(format!("{enum_name}::{variant} {method} {ready}")));
+        }
+
+        fn on_bytes(
+            enum_name: &'static str,
+            variant: &'static str,
+            method: &'static str,
+            n: usize,
+        ) {
+            EVENTS.with(|e| e.borrow_mut().push(format!("{enum_name}::{variant} {method} {n}")));
+        }
+    }
+
+    #[derive(Future, AsyncRead, AsyncWrite)]
+    #[futures_enum(observe = "Observer")]
+    enum Enum<A, B> {
+        A(A),
+        B(B),
+    }
+
+    fn take() -> Vec<String> {
+        EVENTS.with(RefCell::take)
+    }
+
+    #[test]
+    fn test() {
+        let f = Enum::<_, future::Ready<u8>>::A(future::pending::<u8>());
+        assert_eq!(f.now_or_never(), None);
+        let f = Enum::<future::Pending<u8>, _>::B(future::ready(1));
+        assert_eq!(f.now_or_never(), Some(1));
+        assert_eq!(take(), ["Enum::A poll false", "Enum::B poll true"]);
+
+        let mut buf = [1; 4];
+        let mut r = Enum::<_, io::Empty>::A(io::repeat(0));
+        assert_eq!(r.read(&mut buf).now_or_never().unwrap().unwrap(), 4);
+        let mut w = Enum::<io::Sink, _>::B(io::sink());
+        assert_eq!(w.write(&buf[..3]).now_or_never().unwrap().unwrap(), 3);
+        assert_eq!(w.flush().now_or_never().unwrap().unwrap(), ());
+        assert_eq!(take(), [
+            "Enum::A poll_read true",
+            "Enum::A poll_read 4",
+            "Enum::B poll_write true",
+            "Enum::B poll_write 3",
+            "Enum::B poll_flush true",
+        ]);
+    }
+}
+
+mod variant {
+    use futures::future;
+    use futures_enum::{Future, OpaqueDebug, VariantName};
+
+    // `future::Pending` and `future::Ready` implement `Debug`, but the inner
+    // values are not required to.
+    struct NotDebug;
+
+    #[derive(Future, VariantName, OpaqueDebug)]
+    enum Enum<A, B> {
+        A(A),
+        B(B),
+    }
+
+    #[test]
+    fn test() {
+        let a = Enum::<_, future::Ready<NotDebug>>::A(future::pending::<NotDebug>());
+        let b = Enum::<future::Pending<NotDebug>, _>::B(future::ready(NotDebug));
+        assert_eq!(a.variant_name(), "A");
+        assert_eq!(b.variant_name(), "B");
+        assert_eq!(a.variant_index(), 0);
+        assert_eq!(b.variant_index(), 1);
+        assert_eq!(format!("{a:?}"), "Enum::A(..)");
+        assert_eq!(format!("{b:?}"), "Enum::B(..)");
+    }
+}
+
+mod catch_unwind {
+    use std::{
+        any::Any,
+        task::{Context, Poll},
+    };
+
+    use futures::{FutureExt as _, Stream as _, StreamExt as _, future, stream};
+    use futures_enum::{Future, Sink, Stream};
+
+    #[derive(Debug)]
+    struct Panic {
+        variant: &'static str,
+        message: String,
+    }
+
+    impl Panic {
+        fn new(variant: &'static str, payload: Box<dyn Any + Send>) -> Self {
+            let message = payload.downcast::<&str>().map(|s| (*s).to_owned()).unwrap_or_default();
+            Self { variant, message }
+        }
+    }
+
+    // `Sink` is not changed by the option.
+    #[derive(Future, Stream, Sink)]
+    #[futures_enum(catch_unwind = "Panic")]
+    enum Enum<A, B> {
+        A(A),
+        B(B),
+    }
+
+    type PanicFuture = future::Lazy<fn(&mut Context<'_>) -> u8>;
+    type PanicStream = stream::PollFn<fn(&mut Context<'_>) -> Poll<Option<u8>>>;
+
+    #[test]
+    fn test() {
+        let f: Enum<_, PanicFuture> = Enum::A(future::ready(1));
+        assert_eq!(f.now_or_never().unwrap().unwrap(), 1);
+        let f: Enum<future::Ready<u8>, PanicFuture> = Enum::B(future::lazy(|_| panic!("boom")));
+        let err = f.now_or_never().unwrap().unwrap_err();
+        assert_eq!((err.variant, &*err.message), ("B", "boom"));
+
+        let s: Enum<_, PanicStream> = Enum::A(stream::iter(vec![1, 2]));
+        let s = EnumCatchUnwind::new(s);
+        let items: Vec<_> = s.map(Result::unwrap).collect().now_or_never().unwrap();
+        assert_eq!(items, [1, 2]);
+        // The stream ends after the panic instead of polling the variant again.
+        let s: Enum<stream::Empty<u8>, PanicStream> = Enum::B(stream::poll_fn(|_| panic!("boom")));
+        let mut s = EnumCatchUnwind::new(s);
+        assert_eq!(s.size_hint(), (0, None));
+        let items: Vec<_> = s.by_ref().collect().now_or_never().unwrap();
+        assert_eq!(items.len(), 1);
+        let err = items[0].as_ref().unwrap_err();
+        assert_eq!((err.variant, &*err.message), ("B", "boom"));
+        assert!(s.next().now_or_never().unwrap().is_none());
+        assert_eq!(s.size_hint(), (0, Some(0)));
+    }
+}
+
+mod budget {
+    use futures::{
+        AsyncBufReadExt as _, AsyncReadExt as _, FutureExt as _, StreamExt as _, io, stream,
+        task::{Context, Poll, noop_waker_ref},
+    };
+    use futures_enum::{AsyncBufRead, AsyncRead, AsyncWrite, Future, Stream};
+
+    // `Future` and `AsyncWrite` ignore the option.
+    #[derive(Future, Stream, AsyncRead, AsyncWrite)]
+    #[futures_enum(budget = 2)]
+    enum Enum<A, B> {
+        A(A),
+        B(B),
+    }
+
+    #[test]
+    fn test() {
+        let mut cx = Context::from_waker(noop_waker_ref());
+        let s: Enum<_, stream::Empty<u8>> = Enum::A(stream::repeat(1));
+        let mut s = EnumBudgetedStream::new(s);
+        assert_eq!(s.poll_next_unpin(&mut cx), Poll::Ready(Some(1)));
+        assert_eq!(s.poll_next_unpin(&mut cx), Poll::Ready(Some(1)));
+        assert_eq!(s.poll_next_unpin(&mut cx), Poll::Pending);
+        assert_eq!(s.poll_next_unpin(&mut cx), Poll::Ready(Some(1)));
+        // `collect` polls until the stream ends, so it yields before that.
+        let s: Enum<_, stream::Empty<u8>> = Enum::A(stream::iter(0..3));
+        assert_eq!(EnumBudgetedStream::new(s).collect::<Vec<_>>().now_or_never(), None);
+        // The enum itself is not affected.
+        let s: Enum<_, stream::Empty<u8>> = Enum::A(stream::iter(0..3));
+        assert_eq!(s.collect::<Vec<_>>().now_or_never(), Some(vec![0, 1, 2]));
+
+        let r: Enum<_, io::Empty> = Enum::A(io::repeat(0));
+        let mut r = EnumBudgetedReader::new(r);
+        let mut buf = [1; 1];
+        assert_eq!(r.read(&mut buf).now_or_never().unwrap().unwrap(), 1);
+        assert_eq!(r.read(&mut buf).now_or_never().unwrap().unwrap(), 1);
+        assert!(r.read(&mut buf).now_or_never().is_none());
+        assert_eq!(r.read(&mut buf).now_or_never().unwrap().unwrap(), 1);
+    }
+
+    // The lifetime of `AsyncBufRead::poll_fill_buf` is renamed.
+    #[derive(AsyncRead, AsyncBufRead)]
+    #[futures_enum(budget = 1)]
+    enum Borrowed<'__a, A> {
+        A(A),
+        B(&'__a [u8]),
+    }
+
+    #[test]
+    fn buf_read() {
+        let r: Borrowed<'_, io::Empty> = Borrowed::B(b"ab");
+        let mut r = BorrowedBudgetedReader::new(r);
+        assert_eq!(r.fill_buf().now_or_never().unwrap().unwrap(), b"ab");
+        assert!(r.fill_buf().now_or_never().is_none());
+        assert_eq!(r.fill_buf().now_or_never().unwrap().unwrap(), b"ab");
+    }
+
+    // Each value has its own budget, so polling one does not use up the budget
+    // of another.
+    #[test]
+    fn interleave() {
+        let mut cx = Context::from_waker(noop_waker_ref());
+        let a: Enum<_, stream::Empty<u8>> = Enum::A(stream::repeat(1));
+        let b: Enum<stream::Empty<u8>, _> = Enum::B(stream::repeat(2));
+        let (mut a, mut b) = (EnumBudgetedStream::new(a), EnumBudgetedStream::new(b));
+        assert_eq!(a.poll_next_unpin(&mut cx), Poll::Ready(Some(1)));
+        assert_eq!(b.poll_next_unpin(&mut cx), Poll::Ready(Some(2)));
+        assert_eq!(a.poll_next_unpin(&mut cx), Poll::Ready(Some(1)));
+        assert_eq!(b.poll_next_unpin(&mut cx), Poll::Ready(Some(2)));
+        assert_eq!(a.poll_next_unpin(&mut cx), Poll::Pending);
+        assert_eq!(b.poll_next_unpin(&mut cx), Poll::Pending);
+        assert_eq!(a.poll_next_unpin(&mut cx), Poll::Ready(Some(1)));
+        assert_eq!(a.poll_next_unpin(&mut cx), Poll::Ready(Some(1)));
+        assert_eq!(b.poll_next_unpin(&mut cx), Poll::Ready(Some(2)));
+        assert_eq!(a.poll_next_unpin(&mut cx), Poll::Pending);
+        assert_eq!(b.poll_next_unpin(&mut cx), Poll::Ready(Some(2)));
+        assert_eq!(b.poll_next_unpin(&mut cx), Poll::Pending);
+    }
+}
+
+mod then {
+    use std::ops::ControlFlow;
+
+    use futures::{FutureExt as _, future};
+    use futures_enum::{Future, Stream};
+
+    #[derive(Future)]
+    #[futures_enum(then = "Self::advance", output = "Result<String, ()>")]
+    enum Connection {
+        Connect(future::Ready<u16>),
+        Handshake(future::Ready<Result<String, ()>>),
+        Done(future::Ready<Result<String, ()>>),
+    }
+
+    impl Connection {
+        fn advance(output: ConnectionOutput) -> ControlFlow<Result<String, ()>, Self> {
+            match output {
+                ConnectionOutput::Connect(0) => ControlFlow::Break(Err(())),
+                ConnectionOutput::Connect(port) => {
+                    ControlFlow::Continue(Self::Handshake(future::ready(Ok(format!(":{port}")))))
+                }
+                ConnectionOutput::Handshake(Ok(addr)) => {
+                    ControlFlow::Continue(Self::Done(future::ready(Ok(format!("tls{addr}")))))
+                }
+                ConnectionOutput::Handshake(Err(e)) => ControlFlow::Break(Err(e)),
+                ConnectionOutput::Done(output) => ControlFlow::Break(output),
+            }
+        }
+    }
+
+    // The output is not the output of any variant. `Stream` ignores the
+    // option.
+    #[derive(Future, Stream)]
+    #[futures_enum(then = "advance", output = "Option<B::Output>")]
+    enum Generic<A: std::future::Future, B> {
+        A(A),
+        B(B),
+    }
+
+    fn advance<A: std::future::Future, B: std::future::Future>(
+        output: GenericOutput<A, B>,
+    ) -> ControlFlow<Option<B::Output>, Generic<A, B>> {
+        match output {
+            GenericOutput::A(_) => ControlFlow::Break(None),
+            GenericOutput::B(x) => ControlFlow::Break(Some(x)),
+        }
+    }
+
+    #[test]
+    fn test() {
+        let c = Connection::Connect(future::ready(443));
+        assert_eq!(c.now_or_never(), Some(Ok("tls:443".to_owned())));
+        let c = Connection::Connect(future::ready(0));
+        assert_eq!(c.now_or_never(), Some(Err(())));
+        let g = Generic::<future::Ready<u8>, _>::B(future::ready(1));
+        assert_eq!(g.now_or_never(), Some(Some(1)));
+        let g = Generic::<_, future::Ready<u8>>::A(future::ready(()));
+        assert_eq!(g.now_or_never(), Some(None));
+    }
+}
+
+mod on_end {
+    use futures::{FutureExt as _, SinkExt as _, Stream as _, StreamExt as _, sink, stream};
+    use futures_enum::{Sink, Stream};
+
+    #[derive(Stream)]
+    #[futures_enum(on_end = "Self::next_page")]
+    enum Pages {
+        First(stream::Iter<std::ops::Range<u32>>),
+        Rest(stream::Iter<std::vec::IntoIter<u32>>),
+    }
+
+    impl Pages {
+        fn next_page(&self) -> Option<Self> {
+            match self {
+                Self::First(_) => Some(Self::Rest(stream::iter(vec![10, 11]))),
+                Self::Rest(_) => None,
+            }
+        }
+    }
+
+    // `Sink` ignores the option.
+    #[derive(Stream, Sink)]
+    #[futures_enum(on_end = "Self::next")]
+    enum Duplex<A, B> {
+        A(A),
+        B(B),
+    }
+
+    impl<A, B> Duplex<A, B> {
+        fn next(&self) -> Option<Self> {
+            match self {
+                Self::A(_) | Self::B(_) => None,
+            }
+        }
+    }
+
+    #[test]
+    fn test() {
+        let s = Pages::First(stream::iter(0..2));
+        assert_eq!(s.size_hint(), (2, None));
+        assert_eq!(s.collect::<Vec<_>>().now_or_never(), Some(vec![0, 1, 10, 11]));
+        let s = Pages::Rest(stream::iter(vec![0, 1]));
+        assert_eq!(s.collect::<Vec<_>>().now_or_never(), Some(vec![0, 1]));
+
+        let s: Duplex<_, stream::Empty<u8>> = Duplex::A(stream::iter(0..2));
+        assert_eq!(s.collect::<Vec<_>>().now_or_never(), Some(vec![0, 1]));
+        let mut s: Duplex<_, sink::Drain<u8>> = Duplex::A(sink::drain());
+        assert!(s.send(1).now_or_never().unwrap().is_ok());
+    }
+}
+
+mod join {
+    use std::future::IntoFuture as _;
+
+    use futures::{
+        FutureExt as _, future,
+        task::{Context, Poll, noop_waker_ref},
+    };
+    use futures_enum::IntoFuture;
+
+    #[derive(IntoFuture)]
+    #[futures_enum(join)]
+    struct Requests<F> {
+        user: future::Ready<String>,
+        posts: F,
+    }
+
+    #[derive(IntoFuture)]
+    #[futures_enum(join)]
+    struct Pair(future::Ready<u8>, future::Ready<bool>);
+
+    #[derive(IntoFuture)]
+    #[futures_enum(join)]
+    struct Array<F, const N: usize> {
+        first: F,
+        rest: future::Ready<[u8; N]>,
+    }
+
+    #[test]
+    fn test() {
+        let mut cx = Context::from_waker(noop_waker_ref());
+        let mut yielded = false;
+        let posts = future::poll_fn(move |_| {
+            if yielded {
+                Poll::Ready(vec![1, 2])
+            } else {
+                yielded = true;
+                Poll::Pending
+            }
+        });
+        let mut requests =
+            Requests { user: future::ready("alice".to_owned()), posts }.into_future();
+        assert!(requests.poll_unpin(&mut cx).is_pending());
+        let Poll::Ready(RequestsOutput { user, posts }) = requests.poll_unpin(&mut cx) else {
+            panic!()
+        };
+        assert_eq!((&*user, posts), ("alice", vec![1, 2]));
+
+        let pair = Pair(future::ready(1), future::ready(true)).into_future();
+        let PairOutput(a, b) = pair.now_or_never().unwrap();
+        assert_eq!((a, b), (1, true));
+
+        let array = Array { first: future::ready(0), rest: future::ready([1, 2]) }.into_future();
+        let ArrayOutput { first, rest } = array.now_or_never().unwrap();
+        assert_eq!((first, rest), (0, [1, 2]));
+    }
+}
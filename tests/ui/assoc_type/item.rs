@@ -0,0 +1,12 @@
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use futures::stream::Iter;
+use futures_enum::Stream;
+
+#[derive(Stream)]
+enum Enum {
+    A(Iter<std::vec::IntoIter<u8>>),
+    B(Iter<std::vec::IntoIter<u16>>),
+}
+
+fn main() {}
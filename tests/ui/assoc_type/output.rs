@@ -0,0 +1,24 @@
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use std::future::Ready;
+
+use futures_enum::Future;
+
+#[derive(Future)]
+enum Enum {
+    A(Ready<u8>),
+    B(Ready<u16>),
+}
+
+// The variants that refer to type parameters are checked where the enum is used.
+#[derive(Future)]
+enum Generic<B> {
+    A(Ready<u8>),
+    B(B),
+}
+
+fn generic() -> impl std::future::Future {
+    Generic::<Ready<u16>>::B(std::future::ready(1))
+}
+
+fn main() {}
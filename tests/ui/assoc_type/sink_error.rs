@@ -0,0 +1,21 @@
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use futures::{
+    Sink,
+    sink::{self, Drain, SinkErrInto},
+};
+use futures_enum::Sink;
+
+// `Sink` has a type parameter, so the variants are checked where the enum is
+// used.
+#[derive(Sink)]
+enum Enum {
+    A(Drain<u8>),
+    B(SinkErrInto<Drain<u8>, u8, Box<dyn std::error::Error>>),
+}
+
+fn sink() -> impl Sink<u8> {
+    Enum::A(sink::drain())
+}
+
+fn main() {}
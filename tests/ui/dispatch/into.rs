@@ -0,0 +1,25 @@
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+#![allow(unused_imports)]
+
+use std::future::Future;
+
+use futures::{AsyncRead, io};
+use futures_enum::dispatch;
+
+#[dispatch(into)]
+fn no_binding(x: bool) -> impl Future {
+    if x { async { 1_i32 } } else { async { 2_u8 } }
+}
+
+#[dispatch(into)]
+fn async_read(x: bool) -> impl AsyncRead {
+    if x { io::empty() } else { io::repeat(0) }
+}
+
+#[dispatch(from)]
+fn unknown(x: bool) -> impl Future<Output = i64> {
+    if x { async { 1_i64 } } else { async { 2_i64 } }
+}
+
+fn main() {}
@@ -0,0 +1,11 @@
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use futures_enum::{AsyncRead, VariantName};
+
+#[derive(AsyncRead)]
+enum A {}
+
+#[derive(VariantName)]
+enum B {}
+
+fn main() {}
@@ -0,0 +1,22 @@
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use futures_enum::{AsyncWrite, OpaqueDebug, Stream};
+
+#[derive(Stream)]
+enum A<S> {
+    A(S),
+    B(S, S),
+}
+
+#[derive(AsyncWrite)]
+enum B<W> {
+    A(W),
+    B { w: W, n: usize },
+}
+
+#[derive(OpaqueDebug)]
+enum C {
+    A(u8, u16),
+}
+
+fn main() {}
@@ -0,0 +1,14 @@
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use futures_enum::{Future, OpaqueDebug, Stream};
+
+#[derive(Future)]
+struct A(std::future::Ready<()>);
+
+#[derive(Stream)]
+struct B(futures::stream::Empty<()>);
+
+#[derive(OpaqueDebug)]
+struct C(u8);
+
+fn main() {}
@@ -0,0 +1,23 @@
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use futures_enum::{Future, Sink, VariantName};
+
+#[derive(Future)]
+enum A<F> {
+    A(F),
+    B,
+}
+
+#[derive(Sink)]
+enum B<S> {
+    A,
+    B(S),
+}
+
+#[derive(VariantName)]
+enum C {
+    A(u8),
+    B,
+}
+
+fn main() {}